@@ -0,0 +1,155 @@
+//! Discord-side voice ingest: demuxes one incoming 48kHz stereo PCM stream
+//! per speaker SSRC (a songbird voice-receive handler hands decoded frames
+//! to its caller keyed by SSRC, one per speaking Discord user) into
+//! per-SSRC jitter buffers, mixes whatever's active down to a single frame
+//! every 20ms, and forwards it to the central [`crate::mixer`] as one
+//! registered source.
+//!
+//! This module only owns the SSRC demux/mix boundary. The actual Discord
+//! gateway/voice-socket connection is out of scope here and lives in the
+//! separate voice-bridge process; that process feeds frames in over the
+//! `PushSsrcAudio` RPC (see `VoiceServiceImpl::push_ssrc_audio`), which
+//! decodes Opus if needed and calls [`DiscordIngest::push_ssrc_frame`] per
+//! tagged SSRC.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tracing::info;
+
+use crate::mixer::{MixerSource, FRAME_SAMPLES};
+
+/// Depth (in whole 20ms frames) a SSRC's jitter buffer fills to before
+/// `mix_next_frame` starts draining it (see `SsrcBuffer::primed`): enough to
+/// absorb ordinary network timing jitter between Discord packets without
+/// adding much latency on top of it.
+const JITTER_TARGET_DEPTH: usize = 3;
+
+/// Hard cap on queued frames per SSRC. A source that's consistently faster
+/// than the 20ms tick (e.g. a burst after a stall) grows its ring past
+/// `JITTER_TARGET_DEPTH`; once it hits this cap the oldest frame is dropped
+/// so a slow consumer can't accumulate unbounded latency.
+const JITTER_MAX_DEPTH: usize = JITTER_TARGET_DEPTH * 4;
+
+/// After this many consecutive ticks with nothing queued for a SSRC, it's
+/// dropped outright instead of silently contributing silence forever.
+const MAX_CONSECUTIVE_EMPTIES: u32 = 250;
+
+struct SsrcBuffer {
+    /// Ring of whole 20ms frames, oldest first.
+    frames: VecDeque<Vec<i16>>,
+    consecutive_empties: u32,
+    /// Set once `frames` has reached `JITTER_TARGET_DEPTH` for the first
+    /// time since this SSRC appeared; `mix_next_frame` holds off draining
+    /// until then so the ring actually has jitter to absorb, instead of
+    /// draining the very first frame the moment it arrives. Sticky once
+    /// set -- a later dip below `JITTER_TARGET_DEPTH` (including running
+    /// fully dry) doesn't reset it, or the buffer would re-accumulate the
+    /// same latency every time the speaker pauses.
+    primed: bool,
+}
+
+impl SsrcBuffer {
+    fn new() -> Self {
+        Self {
+            frames: VecDeque::new(),
+            consecutive_empties: 0,
+            primed: false,
+        }
+    }
+}
+
+pub struct DiscordIngest {
+    ssrc_buffers: Mutex<HashMap<u32, SsrcBuffer>>,
+}
+
+impl DiscordIngest {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            ssrc_buffers: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Appends one decoded 48kHz stereo PCM frame for `ssrc`, creating its
+    /// jitter buffer lazily on first use and dropping the oldest queued
+    /// frame if the ring has grown past `JITTER_MAX_DEPTH`.
+    pub async fn push_ssrc_frame(&self, ssrc: u32, pcm: &[i16]) {
+        let mut buffers = self.ssrc_buffers.lock().await;
+        let buf = buffers.entry(ssrc).or_insert_with(SsrcBuffer::new);
+        while buf.frames.len() >= JITTER_MAX_DEPTH {
+            buf.frames.pop_front();
+        }
+        buf.frames.push_back(pcm.to_vec());
+        buf.consecutive_empties = 0;
+    }
+
+    /// Pops one `FRAME_SAMPLES`-sized frame from each SSRC that has one
+    /// ready and sums them, clamping to i16 range. A SSRC still priming (see
+    /// `SsrcBuffer::primed`) contributes nothing and isn't drained from yet,
+    /// so its ring can fill past `JITTER_TARGET_DEPTH` before frames start
+    /// flowing out. A primed SSRC with nothing queued this tick contributes
+    /// silence and has its empty-tick streak bumped; once that streak reaches
+    /// `MAX_CONSECUTIVE_EMPTIES` the SSRC is dropped entirely. Returns
+    /// `None` when no SSRC had a frame ready, so the caller can skip this
+    /// tick rather than feeding the mixer continuous silence.
+    async fn mix_next_frame(&self) -> Option<Vec<i16>> {
+        let mut buffers = self.ssrc_buffers.lock().await;
+
+        let mut accum = [0i32; FRAME_SAMPLES];
+        let mut any = false;
+        buffers.retain(|_, buf| {
+            if !buf.primed {
+                if buf.frames.len() >= JITTER_TARGET_DEPTH {
+                    buf.primed = true;
+                } else {
+                    buf.consecutive_empties += 1;
+                    return buf.consecutive_empties < MAX_CONSECUTIVE_EMPTIES;
+                }
+            }
+
+            match buf.frames.pop_front() {
+                Some(frame) => {
+                    any = true;
+                    buf.consecutive_empties = 0;
+                    let n = frame.len().min(FRAME_SAMPLES);
+                    for (slot, sample) in accum.iter_mut().zip(&frame[..n]) {
+                        *slot += *sample as i32;
+                    }
+                }
+                None => {
+                    buf.consecutive_empties += 1;
+                }
+            }
+            buf.consecutive_empties < MAX_CONSECUTIVE_EMPTIES
+        });
+
+        if !any {
+            return None;
+        }
+
+        Some(
+            accum
+                .iter()
+                .map(|&s| s.clamp(i16::MIN as i32, i16::MAX as i32) as i16)
+                .collect(),
+        )
+    }
+}
+
+/// Runs the 20ms mix tick until `mixer_source`'s channel is closed.
+pub fn spawn(ingest: Arc<DiscordIngest>, mixer_source: MixerSource) {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(Duration::from_millis(20));
+        loop {
+            tick.tick().await;
+            if let Some(frame) = ingest.mix_next_frame().await {
+                if mixer_source.send(frame).await.is_err() {
+                    info!("discord_ingest: mixer source closed, stopping");
+                    break;
+                }
+            }
+        }
+    });
+}
@@ -1,13 +1,169 @@
-use std::io;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
 use tracing::{Event, Subscriber};
 use tracing_subscriber::{
-    fmt::{format::Writer, FmtContext, FormatEvent, FormatFields},
+    fmt::{format::Writer, FmtContext, FormatEvent, FormatFields, MakeWriter},
     registry::LookupSpan,
     EnvFilter,
 };
 
+/// 日志行的输出格式，由 `TSBOT_LOG_FORMAT` 配置
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LogFormat {
+    /// 人类可读的 `[时间] [级别] [组件] 消息` 文本行（默认）
+    Text,
+    /// 每条日志一行 JSON，便于被日志采集/查询系统摄入
+    Json,
+}
+
+fn parse_log_format(raw: &str) -> LogFormat {
+    match raw.trim().to_lowercase().as_str() {
+        "json" => LogFormat::Json,
+        _ => LogFormat::Text,
+    }
+}
+
+/// 将 `tracing` 事件记录的字段收集为 `serde_json::Map`，供 JSON 格式化使用
+#[derive(Default)]
+struct JsonFieldVisitor {
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl tracing::field::Visit for JsonFieldVisitor {
+    fn record_f64(&mut self, field: &tracing::field::Field, value: f64) {
+        self.fields.insert(
+            field.name().to_string(),
+            serde_json::Number::from_f64(value)
+                .map(serde_json::Value::Number)
+                .unwrap_or(serde_json::Value::Null),
+        );
+    }
+
+    fn record_i64(&mut self, field: &tracing::field::Field, value: i64) {
+        self.fields
+            .insert(field.name().to_string(), serde_json::Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &tracing::field::Field, value: u64) {
+        self.fields
+            .insert(field.name().to_string(), serde_json::Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &tracing::field::Field, value: bool) {
+        self.fields
+            .insert(field.name().to_string(), serde_json::Value::from(value));
+    }
+
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        self.fields
+            .insert(field.name().to_string(), serde_json::Value::from(value));
+    }
+
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        self.fields.insert(
+            field.name().to_string(),
+            serde_json::Value::from(format!("{:?}", value)),
+        );
+    }
+}
+
+/// 文本模式下一行日志由这些 token 拼接而成，解析自 `TSBOT_LOG_PATTERN`
+enum PatternToken {
+    Literal(String),
+    /// 内部携带一个 `chrono` strftime 子格式
+    TimeStamp(String),
+    Level,
+    Target,
+    ThreadId,
+    ThreadName,
+    File,
+    Line,
+    Message,
+}
+
+const DEFAULT_LOG_PATTERN: &str = "[%TimeStamp%] [%Level%] [%Target%]: %Message%";
+
+/// 解析形如 `"[%TimeStamp(%H:%M:%S)%] [%ThreadID%] [%Level%] [%Target%]: %Message%"` 的模式串，
+/// 只解析一次，`format_event` 之后按 token 顺序写入，避免每条日志都重新解析格式串。
+fn parse_pattern(pattern: &str) -> Vec<PatternToken> {
+    let mut tokens = Vec::new();
+    let mut literal = String::new();
+    let mut rest = pattern;
+
+    while let Some(pct) = rest.find('%') {
+        literal.push_str(&rest[..pct]);
+        let after = &rest[pct + 1..];
+        // TimeStamp(...) 的子格式本身含 '%'，要到 ")%" 才算闭合
+        let end = if after.starts_with("TimeStamp(") {
+            after.find(")%").map(|i| i + 1)
+        } else {
+            after.find('%')
+        };
+        let Some(end) = end else {
+            // 没有闭合的 '%'，剩余内容当作字面量
+            literal.push('%');
+            rest = after;
+            break;
+        };
+        let name = &after[..end];
+        if !literal.is_empty() {
+            tokens.push(PatternToken::Literal(std::mem::take(&mut literal)));
+        }
+        tokens.push(parse_pattern_token(name));
+        rest = &after[end + 1..];
+    }
+    literal.push_str(rest);
+    if !literal.is_empty() {
+        tokens.push(PatternToken::Literal(literal));
+    }
+    tokens
+}
+
+fn parse_pattern_token(name: &str) -> PatternToken {
+    if let Some(sub) = name.strip_prefix("TimeStamp") {
+        let sub = sub.trim();
+        let fmt = match sub.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+            Some(inner) if !inner.is_empty() => inner.to_string(),
+            _ => "%Y-%m-%d %H:%M:%S".to_string(),
+        };
+        return PatternToken::TimeStamp(fmt);
+    }
+    match name {
+        "Level" => PatternToken::Level,
+        "Target" => PatternToken::Target,
+        "ThreadID" => PatternToken::ThreadId,
+        "ThreadName" => PatternToken::ThreadName,
+        "File" => PatternToken::File,
+        "Line" => PatternToken::Line,
+        "Message" => PatternToken::Message,
+        other => PatternToken::Literal(format!("%{}%", other)),
+    }
+}
+
 /// TSBot 统一日志格式化器
-pub struct TSBotFormatter;
+pub struct TSBotFormatter {
+    format: LogFormat,
+    pattern: Vec<PatternToken>,
+}
+
+impl TSBotFormatter {
+    fn from_env() -> Self {
+        let raw = std::env::var("TSBOT_LOG_FORMAT").unwrap_or_default();
+        let pattern_raw = std::env::var("TSBOT_LOG_PATTERN").unwrap_or_default();
+        let pattern_raw = if pattern_raw.trim().is_empty() {
+            DEFAULT_LOG_PATTERN
+        } else {
+            pattern_raw.trim()
+        };
+        Self {
+            format: parse_log_format(&raw),
+            pattern: parse_pattern(pattern_raw),
+        }
+    }
+}
 
 impl<S, N> FormatEvent<S, N> for TSBotFormatter
 where
@@ -20,34 +176,268 @@ where
         mut writer: Writer<'_>,
         event: &Event<'_>,
     ) -> std::fmt::Result {
-        // 获取时间戳
+        let meta = event.metadata();
+
+        if self.format == LogFormat::Json {
+            let now = chrono::Local::now();
+            let mut visitor = JsonFieldVisitor::default();
+            event.record(&mut visitor);
+
+            let line = serde_json::json!({
+                "timestamp": now.to_rfc3339(),
+                "level": meta.level().to_string(),
+                "target": meta.target(),
+                "fields": visitor.fields,
+            });
+
+            return writeln!(writer, "{}", line);
+        }
+
         let now = chrono::Local::now();
-        let timestamp = now.format("%Y-%m-%d %H:%M:%S");
-        
-        // 获取日志级别
-        let level = event.metadata().level();
-        
-        // 写入统一格式: [时间] [级别] [组件] 消息
-        write!(writer, "[{}] [{}] [voice] ", timestamp, level)?;
-        
-        // 写入消息内容
-        ctx.field_format().format_fields(writer.by_ref(), event)?;
-        
+        let thread = std::thread::current();
+
+        for token in &self.pattern {
+            match token {
+                PatternToken::Literal(s) => write!(writer, "{}", s)?,
+                PatternToken::TimeStamp(fmt) => write!(writer, "{}", now.format(fmt))?,
+                PatternToken::Level => write!(writer, "{}", meta.level())?,
+                PatternToken::Target => write!(writer, "{}", meta.target())?,
+                PatternToken::ThreadId => write!(writer, "{:?}", thread.id())?,
+                PatternToken::ThreadName => write!(writer, "{}", thread.name().unwrap_or("<unnamed>"))?,
+                PatternToken::File => write!(writer, "{}", meta.file().unwrap_or("<unknown>"))?,
+                PatternToken::Line => match meta.line() {
+                    Some(l) => write!(writer, "{}", l)?,
+                    None => write!(writer, "?")?,
+                },
+                PatternToken::Message => ctx.field_format().format_fields(writer.by_ref(), event)?,
+            }
+        }
+
         writeln!(writer)
     }
 }
 
+/// 日志文件的滚动策略，由 `TSBOT_LOG_ROTATE` 配置
+#[derive(Clone, Copy, Debug)]
+enum LogRotate {
+    /// 不滚动，一直追加写入同一个文件
+    Never,
+    /// 每天第一次写入时滚动一次
+    Daily,
+    /// 文件大小超过阈值（字节）时滚动
+    Size(u64),
+}
+
+fn parse_log_rotate(raw: &str) -> LogRotate {
+    let raw = raw.trim().to_lowercase();
+    if raw.is_empty() || raw == "never" || raw == "none" {
+        return LogRotate::Never;
+    }
+    if raw == "daily" {
+        return LogRotate::Daily;
+    }
+    if let Some(size) = raw.strip_prefix("size:") {
+        if let Some(bytes) = parse_size_bytes(size) {
+            return LogRotate::Size(bytes);
+        }
+    }
+    LogRotate::Never
+}
+
+fn parse_size_bytes(raw: &str) -> Option<u64> {
+    let raw = raw.trim();
+    let (digits, mult): (&str, u64) = if let Some(n) = raw.strip_suffix("KB") {
+        (n, 1024)
+    } else if let Some(n) = raw.strip_suffix("MB") {
+        (n, 1024 * 1024)
+    } else if let Some(n) = raw.strip_suffix("GB") {
+        (n, 1024 * 1024 * 1024)
+    } else {
+        (raw, 1)
+    };
+    digits.trim().parse::<u64>().ok().map(|n| n * mult)
+}
+
+/// 支持按天或按大小滚动的日志文件，每次写入前检查是否需要先改名再重开
+struct RotatingFile {
+    path: PathBuf,
+    rotate: LogRotate,
+    file: File,
+    bytes_written: u64,
+    current_date: chrono::NaiveDate,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf, rotate: LogRotate) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let bytes_written = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self {
+            path,
+            rotate,
+            file,
+            bytes_written,
+            current_date: chrono::Local::now().date_naive(),
+        })
+    }
+
+    fn rotate_if_needed(&mut self, incoming_len: usize) {
+        let should_rotate = match self.rotate {
+            LogRotate::Never => false,
+            LogRotate::Daily => chrono::Local::now().date_naive() != self.current_date,
+            LogRotate::Size(limit) => self.bytes_written + incoming_len as u64 > limit,
+        };
+        if !should_rotate {
+            return;
+        }
+
+        let _ = self.file.flush();
+        let ts = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+        let rotated_name = match self.path.file_stem().and_then(|s| s.to_str()) {
+            Some(stem) => format!("{}_{}.log", stem, ts),
+            None => format!("voice_{}.log", ts),
+        };
+        let rotated_path = self.path.with_file_name(rotated_name);
+
+        if fs::rename(&self.path, &rotated_path).is_ok() {
+            if let Ok(f) = OpenOptions::new().create(true).append(true).open(&self.path) {
+                self.file = f;
+                self.bytes_written = 0;
+            }
+        }
+        self.current_date = chrono::Local::now().date_naive();
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.rotate_if_needed(buf.len());
+        let n = self.file.write(buf)?;
+        self.bytes_written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// 同时写入 stdout 和（可选的）滚动日志文件的 appender
+#[derive(Clone)]
+struct AppenderWriter {
+    file: Option<Arc<Mutex<RotatingFile>>>,
+}
+
+impl Write for AppenderWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        io::stdout().write_all(buf)?;
+        if let Some(file) = &self.file {
+            if let Ok(mut f) = file.lock() {
+                let _ = f.write_all(buf);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()?;
+        if let Some(file) = &self.file {
+            if let Ok(mut f) = file.lock() {
+                let _ = f.flush();
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for AppenderWriter {
+    type Writer = AppenderWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+fn build_appender() -> AppenderWriter {
+    let log_file = std::env::var("TSBOT_LOG_FILE").unwrap_or_default();
+    let log_file = log_file.trim();
+    if log_file.is_empty() {
+        return AppenderWriter { file: None };
+    }
+
+    let rotate_raw = std::env::var("TSBOT_LOG_ROTATE").unwrap_or_default();
+    let rotate = parse_log_rotate(&rotate_raw);
+
+    match RotatingFile::open(PathBuf::from(log_file), rotate) {
+        Ok(f) => AppenderWriter {
+            file: Some(Arc::new(Mutex::new(f))),
+        },
+        Err(e) => {
+            eprintln!("[voice] failed to open TSBOT_LOG_FILE={}: {}", log_file, e);
+            AppenderWriter { file: None }
+        }
+    }
+}
+
+/// 持有后台写线程的 guard，drop 时会把缓冲区中尚未落盘的日志行刷出。
+/// 必须存活到进程退出前，否则非阻塞模式下尾部日志会被静默丢弃。
+static LOG_WORKER_GUARD: std::sync::OnceLock<Mutex<Option<tracing_appender::non_blocking::WorkerGuard>>> =
+    std::sync::OnceLock::new();
+
+fn is_truthy(raw: &str) -> bool {
+    matches!(raw.trim().to_lowercase().as_str(), "1" | "true" | "yes" | "on")
+}
+
 /// 初始化统一日志配置
 pub fn init_logger() {
     use tracing_subscriber::fmt;
-    
+
     let log_level = std::env::var("TSBOT_LOG_LEVEL").unwrap_or_else(|_| "info".to_string());
     let log_level = log_level.trim().to_lowercase();
     let log_level = if log_level.is_empty() { "info".to_string() } else { log_level };
-    
-    fmt()
-        .event_format(TSBotFormatter)
-        .with_env_filter(EnvFilter::new(format!("voice_service={}", log_level)))
-        .with_writer(io::stdout)
-        .init();
+
+    // 单独一个级别名（如 "debug"）沿用旧行为，只作用于 voice_service 自身；
+    // 包含 "=" 的值当作完整的、逗号分隔的按 target 过滤指令直接交给 EnvFilter，
+    // 例如 "voice_service=debug,grpc=warn,tonic=error"。
+    let env_filter = if log_level.contains('=') {
+        EnvFilter::new(log_level)
+    } else {
+        EnvFilter::new(format!("voice_service={}", log_level))
+    };
+    let appender = build_appender();
+
+    // 实时语音链路不能被一次阻塞的 write! 拖住，TSBOT_LOG_ASYNC 开启后
+    // 日志行先进入 tracing_appender 的有界队列，由后台线程落盘/打印。
+    if is_truthy(&std::env::var("TSBOT_LOG_ASYNC").unwrap_or_default()) {
+        let (non_blocking, guard) = tracing_appender::non_blocking(appender);
+        LOG_WORKER_GUARD
+            .get_or_init(|| Mutex::new(None))
+            .lock()
+            .map(|mut g| *g = Some(guard))
+            .ok();
+
+        fmt()
+            .event_format(TSBotFormatter::from_env())
+            .with_env_filter(env_filter)
+            .with_writer(non_blocking)
+            .init();
+    } else {
+        fmt()
+            .event_format(TSBotFormatter::from_env())
+            .with_env_filter(env_filter)
+            .with_writer(appender)
+            .init();
+    }
+}
+
+/// 优雅退出前调用，确保异步日志 worker 把缓冲区中剩余的记录写完。
+/// 同步模式下是无操作的。
+pub fn shutdown() {
+    if let Some(guard) = LOG_WORKER_GUARD.get() {
+        if let Ok(mut g) = guard.lock() {
+            g.take();
+        }
+    }
 }
@@ -1,6 +1,7 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::env;
 use std::fs;
+use std::io::{Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::pin::Pin;
@@ -8,22 +9,30 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Result};
-use audiopus::coder::Encoder;
+use audiopus::coder::Decoder;
 use futures::{FutureExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
 use tokio::sync::{broadcast, mpsc, watch, Mutex};
 use tokio_stream::wrappers::{BroadcastStream, TcpListenerStream};
 use tokio_util::sync::CancellationToken;
-use tonic::{Request, Response, Status};
+use tonic::{Request, Response, Status, Streaming};
 use tracing::{error, info, warn};
 
+mod discord_ingest;
 mod logger;
+mod metrics;
+mod mixer;
+#[cfg(all(target_os = "linux", feature = "mpris"))]
+mod mpris;
+mod resampler;
+mod rpc_trace;
+mod test_source;
 
 use tsclientlib::{Connection, DisconnectOptions, Identity, StreamItem};
-use tsproto_packets::packets::{AudioData, CodecType, Direction, Flags, OutAudio, OutCommand, OutPacket, PacketType};
+use tsproto_packets::packets::{AudioData, Direction, Flags, OutCommand, OutPacket, PacketType};
 use tsclientlib::{events, MessageTarget};
-use tsclientlib::ChannelId;
+use tsclientlib::{ChannelId, ClientId};
 
 pub mod tsbot {
     pub mod voice {
@@ -47,6 +56,30 @@ struct SharedStatus {
     fx_swap_lr: bool,
     fx_bass_db: f32,
     fx_reverb_mix: f32,
+    /// EBU R128 loudness normalization, applied in `playback_loop` right
+    /// before the FX chain so tracks from different sources land at a
+    /// consistent perceived volume. See [`LoudnessNormalizer`].
+    loudness_enabled: bool,
+    loudness_target_lufs: f32,
+    loudness_max_gain_db: f32,
+    /// Ceiling (dBTP) the always-on look-ahead [`TruePeakLimiter`] holds the
+    /// signal under, last in the FX chain regardless of `loudness_enabled`.
+    limiter_ceiling_db: f32,
+    /// Opus encoder pipeline, read live by `mixer` each tick so operators can
+    /// retune bandwidth/latency per channel without restarting playback. 0
+    /// bitrate means "let libopus pick" (`Bitrate::Auto`).
+    enc_bitrate_bps: i32,
+    enc_vbr: bool,
+    enc_fec: bool,
+    enc_packet_loss_percent: i32,
+    enc_complexity: i32,
+    /// EncoderApplication: 1=VOIP, 2=AUDIO.
+    enc_application: i32,
+    /// EncoderCodec: 1=OPUS_VOICE, 2=OPUS_MUSIC.
+    enc_codec: i32,
+    /// Discontinuous transmission: cheap near-silent frames during gaps
+    /// instead of full-rate encoding. Only meaningful alongside VOIP.
+    enc_dtx: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,6 +91,20 @@ struct PersistedVoiceState {
     fx_swap_lr: bool,
     fx_bass_db: f32,
     fx_reverb_mix: f32,
+    loudness_enabled: bool,
+    loudness_target_lufs: f32,
+    loudness_max_gain_db: f32,
+    limiter_ceiling_db: f32,
+    enc_bitrate_bps: i32,
+    enc_vbr: bool,
+    enc_fec: bool,
+    enc_packet_loss_percent: i32,
+    enc_complexity: i32,
+    enc_application: i32,
+    enc_codec: i32,
+    enc_dtx: bool,
+    queue_items: Vec<QueueItemData>,
+    loop_mode: LoopMode,
 }
 
 impl Default for PersistedVoiceState {
@@ -69,6 +116,20 @@ impl Default for PersistedVoiceState {
             fx_swap_lr: false,
             fx_bass_db: 0.0,
             fx_reverb_mix: 0.0,
+            loudness_enabled: false,
+            loudness_target_lufs: -18.0,
+            loudness_max_gain_db: 12.0,
+            limiter_ceiling_db: -1.0,
+            enc_bitrate_bps: 0,
+            enc_vbr: true,
+            enc_fec: false,
+            enc_packet_loss_percent: 0,
+            enc_complexity: 10,
+            enc_application: 2,
+            enc_codec: 2,
+            enc_dtx: false,
+            queue_items: Vec::new(),
+            loop_mode: LoopMode::Off,
         }
     }
 }
@@ -82,6 +143,136 @@ impl PersistedVoiceState {
             fx_swap_lr: st.fx_swap_lr,
             fx_bass_db: st.fx_bass_db,
             fx_reverb_mix: st.fx_reverb_mix,
+            loudness_enabled: st.loudness_enabled,
+            loudness_target_lufs: st.loudness_target_lufs,
+            loudness_max_gain_db: st.loudness_max_gain_db,
+            limiter_ceiling_db: st.limiter_ceiling_db,
+            enc_bitrate_bps: st.enc_bitrate_bps,
+            enc_vbr: st.enc_vbr,
+            enc_fec: st.enc_fec,
+            enc_packet_loss_percent: st.enc_packet_loss_percent,
+            enc_complexity: st.enc_complexity,
+            enc_application: st.enc_application,
+            enc_codec: st.enc_codec,
+            enc_dtx: st.enc_dtx,
+            queue_items: Vec::new(),
+            loop_mode: LoopMode::Off,
+        }
+    }
+
+    fn with_queue(mut self, q: &QueueState) -> Self {
+        self.queue_items = q.items.iter().cloned().collect();
+        self.loop_mode = q.loop_mode;
+        self
+    }
+}
+
+/// LoopMode mirrors `SetLoopModeRequest.mode`/`QueueResponse.loop_mode` (plain
+/// int32 on the wire, see proto/voice.proto) but is kept as a real enum on the
+/// Rust side since it drives branching logic in the queue-advance path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum LoopMode {
+    Off,
+    Track,
+    Queue,
+}
+
+impl Default for LoopMode {
+    fn default() -> Self {
+        LoopMode::Off
+    }
+}
+
+impl LoopMode {
+    fn from_i32(v: i32) -> Self {
+        match v {
+            1 => LoopMode::Track,
+            2 => LoopMode::Queue,
+            _ => LoopMode::Off,
+        }
+    }
+
+    fn to_i32(self) -> i32 {
+        match self {
+            LoopMode::Off => 0,
+            LoopMode::Track => 1,
+            LoopMode::Queue => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct QueueItemData {
+    title: String,
+    source_url: String,
+}
+
+impl From<&QueueItemData> for voicev1::QueueItem {
+    fn from(item: &QueueItemData) -> Self {
+        voicev1::QueueItem {
+            title: item.title.clone(),
+            source_url: item.source_url.clone(),
+        }
+    }
+}
+
+/// Upcoming tracks plus enough play history to support `Previous`. Guarded by
+/// its own mutex alongside `playback` on `VoiceServiceImpl`, the same pattern
+/// used for `status`.
+#[derive(Default)]
+struct QueueState {
+    items: VecDeque<QueueItemData>,
+    /// Most-recently-finished tracks, most recent last; capped so it can't
+    /// grow unbounded across a long-running session.
+    history: VecDeque<QueueItemData>,
+    loop_mode: LoopMode,
+}
+
+const QUEUE_HISTORY_CAP: usize = 50;
+
+impl QueueState {
+    fn push_history(&mut self, item: QueueItemData) {
+        self.history.push_back(item);
+        while self.history.len() > QUEUE_HISTORY_CAP {
+            self.history.pop_front();
+        }
+    }
+
+    /// Decide what plays next after a track finishes, applying `loop_mode`.
+    /// Returns `None` when the queue is exhausted and playback should go idle.
+    fn take_next_on_finish(&mut self, just_finished: &QueueItemData) -> Option<QueueItemData> {
+        match self.loop_mode {
+            LoopMode::Track => Some(just_finished.clone()),
+            LoopMode::Queue => {
+                if let Some(next) = self.items.pop_front() {
+                    Some(next)
+                } else if !self.history.is_empty() {
+                    // Queue loop with nothing left queued: replay the whole
+                    // history (oldest first) as the new queue.
+                    self.items = self.history.drain(..).collect();
+                    self.items.pop_front()
+                } else {
+                    None
+                }
+            }
+            LoopMode::Off => self.items.pop_front(),
+        }
+    }
+
+    /// Read-only counterpart of [`Self::take_next_on_finish`] used by
+    /// `playback_loop` to decide, ahead of the real transition, whether it's
+    /// worth spawning a decoder for the next track to crossfade into. Does
+    /// not mutate history/queue; the real pop still happens via
+    /// `take_next_on_finish` once the crossfade is actually committed to.
+    fn peek_next(&self, just_finished: &QueueItemData) -> Option<QueueItemData> {
+        match self.loop_mode {
+            LoopMode::Track => Some(just_finished.clone()),
+            LoopMode::Queue => self
+                .items
+                .front()
+                .cloned()
+                .or_else(|| self.history.front().cloned()),
+            LoopMode::Off => self.items.front().cloned(),
         }
     }
 }
@@ -155,12 +346,532 @@ impl SimpleReverb {
     }
 }
 
+/// Direct Form I biquad filter, used to build the two ITU-R BS.1770 /
+/// EBU R128 K-weighting prefilters in [`LoudnessNormalizer`]. One instance
+/// per channel so left/right keep independent filter state.
+struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl Biquad {
+    fn new(b0: f32, b1: f32, b2: f32, a0: f32, a1: f32, a2: f32) -> Self {
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+            x1: 0.0,
+            x2: 0.0,
+            y1: 0.0,
+            y2: 0.0,
+        }
+    }
+
+    /// RBJ "Audio EQ Cookbook" high-shelf with shelf slope S=1: the first
+    /// R128 prefilter stage, a gentle boost above `freq_hz` approximating
+    /// the head's effect on an incoming sound field.
+    fn high_shelf(fs: f32, freq_hz: f32, gain_db: f32) -> Self {
+        let a = 10f32.powf(gain_db / 40.0);
+        let w0 = 2.0 * std::f32::consts::PI * freq_hz / fs;
+        let (sin_w0, cos_w0) = w0.sin_cos();
+        let alpha = sin_w0 / 2.0 * 2f32.sqrt();
+        let sqrt_a = a.sqrt();
+        Self::new(
+            a * ((a + 1.0) + (a - 1.0) * cos_w0 + 2.0 * sqrt_a * alpha),
+            -2.0 * a * ((a - 1.0) + (a + 1.0) * cos_w0),
+            a * ((a + 1.0) + (a - 1.0) * cos_w0 - 2.0 * sqrt_a * alpha),
+            (a + 1.0) - (a - 1.0) * cos_w0 + 2.0 * sqrt_a * alpha,
+            2.0 * ((a - 1.0) - (a + 1.0) * cos_w0),
+            (a + 1.0) - (a - 1.0) * cos_w0 - 2.0 * sqrt_a * alpha,
+        )
+    }
+
+    /// RBJ high-pass: the second R128 prefilter stage, rolling off below
+    /// `freq_hz` so sub-bass rumble doesn't count toward measured loudness.
+    fn high_pass(fs: f32, freq_hz: f32, q: f32) -> Self {
+        let w0 = 2.0 * std::f32::consts::PI * freq_hz / fs;
+        let (sin_w0, cos_w0) = w0.sin_cos();
+        let alpha = sin_w0 / (2.0 * q);
+        Self::new(
+            (1.0 + cos_w0) / 2.0,
+            -(1.0 + cos_w0),
+            (1.0 + cos_w0) / 2.0,
+            1.0 + alpha,
+            -2.0 * cos_w0,
+            1.0 - alpha,
+        )
+    }
+
+    fn process(&mut self, x: f32) -> f32 {
+        let y = self.b0 * x + self.b1 * self.x1 + self.b2 * self.x2 - self.a1 * self.y1 - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
+
+const LOUDNESS_BLOCK_SAMPLES: usize = 48000 * 400 / 1000;
+/// True-peak ceiling the limiter in [`LoudnessNormalizer::apply_gain`] holds
+/// make-up gain under, a hair below full scale so the existing clip counter
+/// downstream stays near zero.
+const LOUDNESS_LIMITER_CEILING: f32 = 0.98;
+
+/// EBU R128 integrated-loudness meter and make-up gain, applied once per
+/// track right before the FX chain so tracks from different sources land at
+/// roughly the same perceived volume regardless of how they were mastered.
+///
+/// K-weights incoming samples through the two R128 prefilter biquads, bins
+/// their energy into 400ms measurement blocks, and keeps a gated integrated
+/// loudness estimate (blocks more than 10 LU below the ungated mean are
+/// discarded, per the standard relative gate) that the make-up gain chases.
+/// Lives for one physical track: `playback_loop` builds a fresh one per
+/// `start_track` call and when a crossfade promotes its pending decoder.
+struct LoudnessNormalizer {
+    shelf_l: Biquad,
+    hp_l: Biquad,
+    shelf_r: Biquad,
+    hp_r: Biquad,
+    block_sum_sq: f32,
+    block_samples: usize,
+    /// Mean-square energy of every closed 400ms block so far; recomputing
+    /// the gated integrated loudness from this each time a block closes is
+    /// cheap since only 150 blocks close per minute of audio.
+    block_mean_sq: Vec<f32>,
+    integrated_lufs: f32,
+    smoothed_gain_db: f32,
+    limiter_gain: f32,
+}
+
+impl LoudnessNormalizer {
+    fn new() -> Self {
+        const FS: f32 = 48000.0;
+        const SHELF_FREQ_HZ: f32 = 1500.0;
+        const SHELF_GAIN_DB: f32 = 4.0;
+        const HIGH_PASS_FREQ_HZ: f32 = 38.0;
+        const HIGH_PASS_Q: f32 = 0.5;
+        Self {
+            shelf_l: Biquad::high_shelf(FS, SHELF_FREQ_HZ, SHELF_GAIN_DB),
+            hp_l: Biquad::high_pass(FS, HIGH_PASS_FREQ_HZ, HIGH_PASS_Q),
+            shelf_r: Biquad::high_shelf(FS, SHELF_FREQ_HZ, SHELF_GAIN_DB),
+            hp_r: Biquad::high_pass(FS, HIGH_PASS_FREQ_HZ, HIGH_PASS_Q),
+            block_sum_sq: 0.0,
+            block_samples: 0,
+            block_mean_sq: Vec::new(),
+            integrated_lufs: 0.0,
+            smoothed_gain_db: 0.0,
+            limiter_gain: 1.0,
+        }
+    }
+
+    /// Feeds one pre-gain stereo sample pair through the K-weighting
+    /// prefilter and into the current 400ms block, closing and folding it
+    /// into the integrated estimate once enough samples have accumulated.
+    fn measure(&mut self, l: f32, r: f32) {
+        let kl = self.hp_l.process(self.shelf_l.process(l));
+        let kr = self.hp_r.process(self.shelf_r.process(r));
+        self.block_sum_sq += kl * kl + kr * kr;
+        self.block_samples += 1;
+        if self.block_samples >= LOUDNESS_BLOCK_SAMPLES {
+            self.block_mean_sq.push(self.block_sum_sq / self.block_samples as f32);
+            self.block_sum_sq = 0.0;
+            self.block_samples = 0;
+            self.recompute_integrated();
+        }
+    }
+
+    fn recompute_integrated(&mut self) {
+        let lufs_of = |mean_sq: f32| -0.691 + 10.0 * mean_sq.max(1e-10).log10();
+
+        let ungated_mean: f32 = self.block_mean_sq.iter().sum::<f32>() / self.block_mean_sq.len() as f32;
+        let threshold_lufs = lufs_of(ungated_mean) - 10.0;
+
+        let (gated_sum, gated_count) = self
+            .block_mean_sq
+            .iter()
+            .filter(|&&ms| lufs_of(ms) >= threshold_lufs)
+            .fold((0.0f32, 0u32), |(sum, count), &ms| (sum + ms, count + 1));
+
+        if gated_count > 0 {
+            self.integrated_lufs = lufs_of(gated_sum / gated_count as f32);
+        }
+    }
+
+    /// Applies the current smoothed make-up gain -- the gap between
+    /// `target_lufs` and the running integrated estimate, capped at
+    /// `max_gain_db` in either direction -- plus a true-peak limiter riding
+    /// behind it, in place, on an already-measured sample pair.
+    fn apply_gain(&mut self, l: &mut f32, r: &mut f32, target_lufs: f32, max_gain_db: f32) {
+        if self.block_mean_sq.is_empty() {
+            // First block still filling: no estimate to act on yet.
+            return;
+        }
+
+        let target_gain_db = (target_lufs - self.integrated_lufs).clamp(-max_gain_db, max_gain_db);
+        // One-pole smoothing over ~2s so the gain doesn't zipper between blocks.
+        let smoothing_alpha = 1.0 / (48000.0 * 2.0);
+        self.smoothed_gain_db += smoothing_alpha * (target_gain_db - self.smoothed_gain_db);
+
+        let gain = 10.0_f32.powf(self.smoothed_gain_db / 20.0);
+        let mut gl = *l * gain;
+        let mut gr = *r * gain;
+
+        // Fast attack when the made-up signal would clear the ceiling, slow
+        // release back toward unity so the limiter doesn't audibly pump.
+        let peak = gl.abs().max(gr.abs());
+        let needed = if peak > LOUDNESS_LIMITER_CEILING {
+            LOUDNESS_LIMITER_CEILING / peak
+        } else {
+            1.0
+        };
+        if needed < self.limiter_gain {
+            self.limiter_gain = needed;
+        } else {
+            let release_alpha = 1.0 / (48000.0 * 0.2);
+            self.limiter_gain = (self.limiter_gain + release_alpha * (needed - self.limiter_gain)).min(needed);
+        }
+        gl *= self.limiter_gain;
+        gr *= self.limiter_gain;
+
+        *l = gl;
+        *r = gr;
+    }
+}
+
+/// Samples of look-ahead the [`TruePeakLimiter`] buffers before deciding its
+/// gain, long enough to catch a transient's leading edge without being
+/// audible as its own delay.
+const LIMITER_LOOKAHEAD_SAMPLES: usize = 48000 * 3 / 1000;
+
+/// Look-ahead true-peak limiter run unconditionally, last in the FX chain,
+/// as the final safety net against whatever gain stacking (bass boost,
+/// width, loudness make-up, volume>100%) would otherwise send over
+/// `limiter_ceiling_db`. Unlike [`LoudnessNormalizer`]'s own reactive
+/// limiter (which only protects the loudness make-up gain, and only while
+/// loudness normalization is enabled), this one delays the signal by
+/// `LIMITER_LOOKAHEAD_SAMPLES` so the gain reduction for an upcoming
+/// transient is already ramped in by the time that transient reaches the
+/// output, instead of clipping its leading edge the way a purely reactive
+/// limiter would. Lives for the whole `playback_loop` task, not reset per
+/// track, since it's a bus-level safety net rather than a per-track
+/// measurement.
+struct TruePeakLimiter {
+    delay_l: VecDeque<f32>,
+    delay_r: VecDeque<f32>,
+    /// Per-sample `max(|l|, |r|)` over the same window as `delay_l`/`delay_r`,
+    /// scanned for its max each sample to decide the gain needed for the
+    /// delayed sample about to leave the window.
+    peaks: VecDeque<f32>,
+    gain: f32,
+    /// Peak gain reduction (dB) applied since the last `audio_encode_diag`
+    /// window; read and reset by `playback_loop` each time that log fires.
+    max_reduction_db: f32,
+}
+
+impl TruePeakLimiter {
+    fn new() -> Self {
+        Self {
+            delay_l: VecDeque::from(vec![0.0; LIMITER_LOOKAHEAD_SAMPLES]),
+            delay_r: VecDeque::from(vec![0.0; LIMITER_LOOKAHEAD_SAMPLES]),
+            peaks: VecDeque::from(vec![0.0; LIMITER_LOOKAHEAD_SAMPLES]),
+            gain: 1.0,
+            max_reduction_db: 0.0,
+        }
+    }
+
+    /// Feeds one incoming stereo sample pair in and returns the
+    /// `LIMITER_LOOKAHEAD_SAMPLES`-delayed pair with gain reduction applied,
+    /// so `ceiling` (linear, e.g. `10f32.powf(-1.0 / 20.0)` for -1 dBTP) is
+    /// honored by the time the now-delayed samples reach the output.
+    fn process(&mut self, l: f32, r: f32, ceiling: f32) -> (f32, f32) {
+        self.peaks.push_back(l.abs().max(r.abs()));
+        self.peaks.pop_front();
+        self.delay_l.push_back(l);
+        self.delay_r.push_back(r);
+        let out_l = self.delay_l.pop_front().unwrap_or(0.0);
+        let out_r = self.delay_r.pop_front().unwrap_or(0.0);
+
+        let window_peak = self.peaks.iter().cloned().fold(0.0f32, f32::max);
+        let needed = if window_peak > ceiling {
+            ceiling / window_peak
+        } else {
+            1.0
+        };
+
+        // Fast attack when the window ahead would clear the ceiling, slow
+        // release back toward unity so the limiter doesn't audibly pump --
+        // same envelope shape as `LoudnessNormalizer::apply_gain`'s limiter.
+        if needed < self.gain {
+            self.gain = needed;
+        } else {
+            let release_alpha = 1.0 / (48000.0 * 0.2);
+            self.gain = (self.gain + release_alpha * (needed - self.gain)).min(needed);
+        }
+
+        let reduction_db = -20.0 * self.gain.max(1e-6).log10();
+        if reduction_db > self.max_reduction_db {
+            self.max_reduction_db = reduction_db;
+        }
+
+        (out_l * self.gain, out_r * self.gain)
+    }
+
+    fn take_max_reduction_db(&mut self) -> f32 {
+        std::mem::replace(&mut self.max_reduction_db, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod dsp_tests {
+    use super::{Biquad, LoudnessNormalizer, TruePeakLimiter, LIMITER_LOOKAHEAD_SAMPLES, LOUDNESS_BLOCK_SAMPLES};
+
+    #[test]
+    fn high_pass_attenuates_dc() {
+        let mut hp = Biquad::high_pass(48000.0, 38.0, 0.5);
+        let mut last = 0.0;
+        for _ in 0..48000 {
+            last = hp.process(1.0);
+        }
+        assert!(last.abs() < 0.01, "dc leaked through: {last}");
+    }
+
+    #[test]
+    fn high_shelf_boosts_high_frequency() {
+        let mut shelf = Biquad::high_shelf(48000.0, 1500.0, 4.0);
+        let mut last = 0.0;
+        // Alternating +1/-1 is a Nyquist-frequency square wave, well above
+        // the 1500Hz shelf corner, so the shelf's full +4dB gain applies.
+        for i in 0..200 {
+            let x = if i % 2 == 0 { 1.0 } else { -1.0 };
+            last = shelf.process(x);
+        }
+        assert!(last.abs() > 1.2 && last.abs() < 2.0, "got {last}");
+    }
+
+    fn measured_lufs_at_amplitude(amp: f32) -> f32 {
+        let mut n = LoudnessNormalizer::new();
+        let freq_hz = 1000.0_f32;
+        for i in 0..(LOUDNESS_BLOCK_SAMPLES * 3) {
+            let t = i as f32 / 48000.0;
+            let s = amp * (2.0 * std::f32::consts::PI * freq_hz * t).sin();
+            n.measure(s, s);
+        }
+        n.integrated_lufs
+    }
+
+    #[test]
+    fn louder_signal_measures_higher_lufs() {
+        let quiet = measured_lufs_at_amplitude(0.05);
+        let loud = measured_lufs_at_amplitude(0.5);
+        assert!(loud > quiet, "loud ({loud}) should measure above quiet ({quiet})");
+    }
+
+    #[test]
+    fn apply_gain_respects_max_gain_clamp() {
+        let mut n = LoudnessNormalizer::new();
+        // A very quiet full block sits well below any plausible target, so
+        // the make-up gain saturates at `max_gain_db`.
+        for _ in 0..LOUDNESS_BLOCK_SAMPLES {
+            n.measure(0.001, 0.001);
+        }
+        let max_gain_db = 6.0;
+        let mut l = 0.0;
+        let mut r = 0.0;
+        // One-pole smoothing is ~2s; give it several seconds to settle.
+        for _ in 0..(48000 * 5) {
+            l = 0.001;
+            r = 0.001;
+            n.apply_gain(&mut l, &mut r, -14.0, max_gain_db);
+        }
+        let applied_gain_db = 20.0 * (l / 0.001).abs().log10();
+        assert!(applied_gain_db <= max_gain_db + 0.1, "gain exceeded clamp: {applied_gain_db}dB");
+    }
+
+    #[test]
+    fn limiter_holds_peaks_under_ceiling() {
+        let mut lim = TruePeakLimiter::new();
+        let ceiling = 0.5;
+        let mut max_out: f32 = 0.0;
+        for i in 0..4800 {
+            let t = i as f32 / 48000.0;
+            let s = 0.9 * (2.0 * std::f32::consts::PI * 440.0 * t).sin();
+            let (l, r) = lim.process(s, s, ceiling);
+            max_out = max_out.max(l.abs()).max(r.abs());
+        }
+        assert!(max_out <= ceiling + 0.01, "peak {max_out} exceeded ceiling {ceiling}");
+    }
+
+    #[test]
+    fn limiter_passes_quiet_signal_at_unity() {
+        let mut lim = TruePeakLimiter::new();
+        let ceiling = 0.9;
+        let mut last = (0.0, 0.0);
+        for _ in 0..(LIMITER_LOOKAHEAD_SAMPLES + 100) {
+            last = lim.process(0.1, 0.1, ceiling);
+        }
+        assert!((last.0 - 0.1).abs() < 1e-3, "quiet signal was attenuated: {}", last.0);
+    }
+}
+
 struct PlaybackControl {
     cancel: CancellationToken,
     paused_tx: watch::Sender<bool>,
+    /// `Some(position_ms)` requests the running `playback_loop` restart its
+    /// decoder at that offset; marked seen (the value itself stays) once
+    /// handled.
+    seek_tx: watch::Sender<Option<i64>>,
     handle: tokio::task::JoinHandle<()>,
 }
 
+/// Sent from `VoiceServiceImpl::{start,stop}_recording` to `ts3_actor`, the
+/// only place holding the `Connection` and therefore the only place that can
+/// see inbound `StreamItem::Audio` packets. `Start`'s `bool` selects
+/// per-speaker output files instead of the default single mixed-down file.
+enum RecordCommand {
+    Start(PathBuf, bool),
+    Stop,
+}
+
+/// Per-speaker Opus decoder and jitter ring for inbound TS3 voice: created
+/// lazily on a client's first packet (mirroring the per-SSRC buffer map
+/// `discord_ingest` keeps for the Discord side) and dropped once
+/// `SPEAKER_SILENCE_TIMEOUT` passes without a new packet.
+struct SpeakerState {
+    decoder: Decoder,
+    /// Decoded 48kHz stereo PCM awaiting the next `record_tick` flush.
+    ring: VecDeque<i16>,
+    last_packet: Instant,
+}
+
+const SPEAKER_SILENCE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// One 20ms 48kHz stereo frame, interleaved -- the same cadence and layout
+/// `mixer::FRAME_SAMPLES` uses for outbound audio.
+const RECORD_FRAME_SAMPLES: usize = mixer::FRAME_SAMPLES;
+
+/// Upper bound on interleaved samples a single Opus packet can decode to (up
+/// to a 120ms stereo frame at 48kHz); reused as scratch space across
+/// packets/clients.
+const OPUS_DECODE_SCRATCH: usize = 11520;
+
+fn rms_i16(samples: &[i16]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum_sq: f64 = samples.iter().map(|&s| (s as f64) * (s as f64)).sum();
+    ((sum_sq / samples.len() as f64).sqrt() / i16::MAX as f64) as f32
+}
+
+/// Builds a 44-byte canonical PCM WAV header. `data_len` is the payload size
+/// in bytes; callers write a zeroed placeholder up front and patch it in
+/// once the final size is known (see `RecordingState::finalize`).
+fn wav_header(data_len: u32, sample_rate: u32, channels: u16, bits_per_sample: u16) -> [u8; 44] {
+    let byte_rate = sample_rate * channels as u32 * (bits_per_sample as u32 / 8);
+    let block_align = channels * (bits_per_sample / 8);
+    let mut h = [0u8; 44];
+    h[0..4].copy_from_slice(b"RIFF");
+    h[4..8].copy_from_slice(&(36 + data_len).to_le_bytes());
+    h[8..12].copy_from_slice(b"WAVE");
+    h[12..16].copy_from_slice(b"fmt ");
+    h[16..20].copy_from_slice(&16u32.to_le_bytes());
+    h[20..22].copy_from_slice(&1u16.to_le_bytes()); // PCM
+    h[22..24].copy_from_slice(&channels.to_le_bytes());
+    h[24..28].copy_from_slice(&sample_rate.to_le_bytes());
+    h[28..32].copy_from_slice(&byte_rate.to_le_bytes());
+    h[32..34].copy_from_slice(&block_align.to_le_bytes());
+    h[34..36].copy_from_slice(&bits_per_sample.to_le_bytes());
+    h[36..40].copy_from_slice(b"data");
+    h[40..44].copy_from_slice(&data_len.to_le_bytes());
+    h
+}
+
+/// A 48kHz stereo PCM WAV file being built up one frame at a time, from
+/// `StartRecording` until `StopRecording` (or speaker silence timeout, in
+/// per-speaker mode) finalizes the header.
+struct RecordingState {
+    file: fs::File,
+    path: PathBuf,
+    samples_written: u64,
+}
+
+impl RecordingState {
+    fn create(path: PathBuf) -> Result<Self> {
+        let mut file = fs::File::create(&path)
+            .map_err(|e| anyhow!("create recording file {} failed: {e}", path.display()))?;
+        file.write_all(&wav_header(0, 48000, 2, 16))
+            .map_err(|e| anyhow!("write recording header failed: {e}"))?;
+        Ok(Self {
+            file,
+            path,
+            samples_written: 0,
+        })
+    }
+
+    fn write_frame(&mut self, samples: &[i16]) -> std::io::Result<()> {
+        let mut buf = Vec::with_capacity(samples.len() * 2);
+        for &s in samples {
+            buf.extend_from_slice(&s.to_le_bytes());
+        }
+        self.file.write_all(&buf)?;
+        self.samples_written += samples.len() as u64;
+        Ok(())
+    }
+
+    fn finalize(mut self) -> std::io::Result<()> {
+        let data_len = (self.samples_written * 2) as u32;
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.write_all(&wav_header(data_len, 48000, 2, 16))?;
+        self.file.flush()
+    }
+}
+
+/// Derives `<stem>_<client_id><ext>` next to `base` for per-speaker output,
+/// e.g. `recording.wav` -> `recording_42.wav`.
+fn per_speaker_path(base: &Path, client_id: u16) -> PathBuf {
+    let stem = base.file_stem().and_then(|s| s.to_str()).unwrap_or("recording");
+    let ext = base.extension().and_then(|s| s.to_str()).unwrap_or("wav");
+    let file_name = format!("{stem}_{client_id}.{ext}");
+    match base.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.join(file_name),
+        _ => PathBuf::from(file_name),
+    }
+}
+
+/// What `StartRecording` is currently doing: either mixing every active
+/// speaker down into one file, or writing each speaker to their own file
+/// (created lazily the first time that speaker is heard).
+enum RecordingMode {
+    Mixed(RecordingState),
+    PerSpeaker {
+        base_path: PathBuf,
+        writers: HashMap<ClientId, RecordingState>,
+    },
+}
+
+impl RecordingMode {
+    fn finalize(self) -> std::io::Result<()> {
+        match self {
+            RecordingMode::Mixed(rs) => rs.finalize(),
+            RecordingMode::PerSpeaker { writers, .. } => {
+                for (_, rs) in writers {
+                    rs.finalize()?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
 struct AvatarUploadState {
     handle: tsclientlib::FiletransferHandle,
     local_path: PathBuf,
@@ -220,11 +931,37 @@ impl Drop for ChildKillOnDrop {
 struct VoiceServiceImpl {
     status: Arc<Mutex<SharedStatus>>,
     playback: Arc<Mutex<Option<PlaybackControl>>>,
+    queue: Arc<Mutex<QueueState>>,
     ts3_audio_tx: mpsc::Sender<OutPacket>,
     ts3_notice_tx: mpsc::Sender<(i32, String)>,
     ts3_cmd_tx: mpsc::Sender<OutCommand>,
+    ts3_record_tx: mpsc::Sender<RecordCommand>,
+    /// Music playback registers a fresh source here per track, and
+    /// `push_audio` registers one for the life of its stream; see `mixer`
+    /// module docs for why nothing encodes/sends directly anymore.
+    mixer: mixer::MixerHandle,
     events_tx: broadcast::Sender<voicev1::Event>,
     persist_tx: mpsc::Sender<PersistedVoiceState>,
+    /// Set only when `TSBOT_DISCORD_INGEST_ENABLE` is on; `push_ssrc_audio`
+    /// forwards tagged frames here, and `discord_ingest::spawn` drains it
+    /// into the mixer. `None` means the RPC is rejected outright.
+    discord_ingest: Option<Arc<discord_ingest::DiscordIngest>>,
+    /// Position of the current track, updated by `playback_loop` roughly
+    /// every 20ms; `0` when nothing is playing.
+    position_ms: Arc<std::sync::atomic::AtomicI64>,
+    /// Depth (in 20ms frames) of the current track's look-ahead PCM queue;
+    /// surfaced in `ts3_actor`'s periodic diagnostic log.
+    buffer_occupancy: Arc<std::sync::atomic::AtomicUsize>,
+    metrics: Arc<metrics::Metrics>,
+    /// Cancel token for whichever `spawn_track_metadata_task` is currently
+    /// broadcasting progress for the track playing right now -- kept up to
+    /// date by `playback_loop` (including across its internal crossfade
+    /// transitions) so `start_track` can cancel it unconditionally before
+    /// starting the next track. Without this, a track that plays to its
+    /// natural end (no explicit `stop_internal()`, so nothing ever cancels
+    /// the old `playback_loop`'s own cancel tree) leaks its metadata task
+    /// forever.
+    track_meta_cancel: Arc<Mutex<Option<CancellationToken>>>,
 }
 
 fn load_persisted_voice_state(path: &Path) -> Option<PersistedVoiceState> {
@@ -272,6 +1009,40 @@ fn emit_playback(
     });
 }
 
+fn emit_queue_changed(events_tx: &broadcast::Sender<voicev1::Event>, q: &QueueState) {
+    let _ = events_tx.send(voicev1::Event {
+        unix_ms: now_unix_ms(),
+        payload: Some(voicev1::event::Payload::Queue(voicev1::QueueChangedEvent {
+            items: q.items.iter().map(voicev1::QueueItem::from).collect(),
+            current_index: -1,
+            loop_mode: q.loop_mode.to_i32(),
+        })),
+    });
+}
+
+fn emit_metadata(events_tx: &broadcast::Sender<voicev1::Event>, meta: TrackMetadata) {
+    let _ = events_tx.send(voicev1::Event {
+        unix_ms: now_unix_ms(),
+        payload: Some(voicev1::event::Payload::Metadata(voicev1::TrackMetadataEvent {
+            title: meta.title,
+            artist: meta.artist,
+            album: meta.album,
+            duration_ms: meta.duration_ms,
+            bitrate_kbps: meta.bitrate_kbps,
+        })),
+    });
+}
+
+fn emit_progress(events_tx: &broadcast::Sender<voicev1::Event>, position_ms: i64, duration_ms: i64) {
+    let _ = events_tx.send(voicev1::Event {
+        unix_ms: now_unix_ms(),
+        payload: Some(voicev1::event::Payload::Progress(voicev1::ProgressEvent {
+            position_ms,
+            duration_ms,
+        })),
+    });
+}
+
 #[tonic::async_trait]
 impl VoiceService for VoiceServiceImpl {
     async fn ping(
@@ -293,49 +1064,14 @@ impl VoiceService for VoiceServiceImpl {
             let _ = self.ts3_notice_tx.try_send((2, r.notice.clone()));
         }
 
-        {
-            let mut st = self.status.lock().await;
-            st.now_playing_title = r.title.clone();
-            st.now_playing_source_url = r.source_url.clone();
-            st.state = 2; // STATE_PLAYING
-        }
-
-        // PlaybackEvent.Type: STARTED=1
-        emit_playback(&self.events_tx, 1, r.title.clone(), r.source_url.clone(), "");
-
-        self.stop_internal().await;
-
-        let (paused_tx, paused_rx) = watch::channel(false);
-        let cancel = CancellationToken::new();
-
-        let status = self.status.clone();
-        let tx = self.ts3_audio_tx.clone();
-        let events_tx = self.events_tx.clone();
-        let title = r.title.clone();
-        let source_url = r.source_url;
-        let cancel_child = cancel.clone();
-
-        let handle = tokio::spawn(async move {
-            let r = playback_loop(source_url.clone(), tx, paused_rx, cancel_child, status).await;
-            match r {
-                Ok(()) => {
-                    // PlaybackEvent.Type: FINISHED=2
-                    emit_playback(&events_tx, 2, title, source_url, "");
-                }
-                Err(e) => {
-                    error!(%e, "playback loop failed");
-                    // PlaybackEvent.Type: ERROR=3
-                    emit_playback(&events_tx, 3, title, source_url, format!("{e}"));
-                }
-            }
-        });
-
-        let mut pb = self.playback.lock().await;
-        *pb = Some(PlaybackControl {
-            cancel,
-            paused_tx,
-            handle,
-        });
+        self.start_track(
+            QueueItemData {
+                title: r.title,
+                source_url: r.source_url,
+            },
+            true,
+        )
+        .await;
 
         Ok(Response::new(voicev1::CommandResponse {
             ok: true,
@@ -443,37 +1179,60 @@ impl VoiceService for VoiceServiceImpl {
 
     async fn skip(
         &self,
-        _req: Request<voicev1::Empty>,
+        req: Request<voicev1::Empty>,
     ) -> std::result::Result<Response<voicev1::CommandResponse>, Status> {
-        self.stop(_req).await
+        self.next(req).await
     }
 
-    async fn send_notice(
+    async fn seek(
         &self,
-        req: Request<voicev1::NoticeRequest>,
+        req: Request<voicev1::SeekRequest>,
     ) -> std::result::Result<Response<voicev1::CommandResponse>, Status> {
-        let r = req.into_inner();
-        if !r.message.is_empty() {
-            let mode = if r.target_mode == 3 { 3 } else { 2 };
-            let _ = self.ts3_notice_tx.try_send((mode, r.message));
-        }
+        let position_ms = req.into_inner().position_ms.max(0);
+
+        let pb = self.playback.lock().await;
+        let Some(pb) = pb.as_ref() else {
+            return Ok(Response::new(voicev1::CommandResponse {
+                ok: false,
+                message: "nothing is playing".to_string(),
+            }));
+        };
+        let _ = pb.seek_tx.send(Some(position_ms));
+
+        // LogEvent.Level: INFO=2
+        emit_log(&self.events_tx, 2, format!("seeking to {position_ms}ms"));
+
         Ok(Response::new(voicev1::CommandResponse {
             ok: true,
             message: "ok".to_string(),
         }))
     }
 
-    async fn set_volume(
+    async fn enqueue(
         &self,
-        req: Request<voicev1::SetVolumeRequest>,
+        req: Request<voicev1::EnqueueRequest>,
     ) -> std::result::Result<Response<voicev1::CommandResponse>, Status> {
-        let v = req.into_inner().volume_percent.clamp(0, 200);
-        let snapshot = {
-            let mut st = self.status.lock().await;
-            st.volume_percent = v;
-            PersistedVoiceState::from_status(&st)
+        let r = req.into_inner();
+        let item = QueueItemData {
+            title: r.title,
+            source_url: r.source_url,
+        };
+
+        let idle = {
+            let st = self.status.lock().await;
+            st.state == 1 // STATE_IDLE
         };
-        let _ = self.persist_tx.try_send(snapshot);
+
+        if idle {
+            // Nothing is playing: start this track immediately instead of
+            // leaving it to sit in the queue.
+            self.start_track(item, true).await;
+        } else {
+            let mut q = self.queue.lock().await;
+            q.items.push_back(item);
+            emit_queue_changed(&self.events_tx, &q);
+            self.persist_queue(&q).await;
+        }
 
         Ok(Response::new(voicev1::CommandResponse {
             ok: true,
@@ -481,26 +1240,221 @@ impl VoiceService for VoiceServiceImpl {
         }))
     }
 
-    async fn get_status(
+    async fn list_queue(
         &self,
         _req: Request<voicev1::Empty>,
-    ) -> std::result::Result<Response<voicev1::StatusResponse>, Status> {
-        let st = self.status.lock().await;
-        Ok(Response::new(voicev1::StatusResponse {
-            state: st.state,
-            now_playing_title: st.now_playing_title.clone(),
-            now_playing_source_url: st.now_playing_source_url.clone(),
-            volume_percent: st.volume_percent,
+    ) -> std::result::Result<Response<voicev1::QueueResponse>, Status> {
+        let q = self.queue.lock().await;
+        Ok(Response::new(voicev1::QueueResponse {
+            items: q.items.iter().map(voicev1::QueueItem::from).collect(),
+            current_index: -1,
+            loop_mode: q.loop_mode.to_i32(),
         }))
     }
 
-    async fn set_audio_fx(
+    async fn remove_at(
         &self,
-        req: Request<voicev1::SetAudioFxRequest>,
+        req: Request<voicev1::RemoveAtRequest>,
     ) -> std::result::Result<Response<voicev1::CommandResponse>, Status> {
-        let r = req.into_inner();
-        let snapshot = {
-            let mut st = self.status.lock().await;
+        let idx = req.into_inner().index;
+        let mut q = self.queue.lock().await;
+        let removed = usize::try_from(idx)
+            .ok()
+            .filter(|i| *i < q.items.len())
+            .map(|i| q.items.remove(i));
+
+        if removed.is_none() {
+            return Ok(Response::new(voicev1::CommandResponse {
+                ok: false,
+                message: "index out of range".to_string(),
+            }));
+        }
+
+        emit_queue_changed(&self.events_tx, &q);
+        self.persist_queue(&q).await;
+
+        Ok(Response::new(voicev1::CommandResponse {
+            ok: true,
+            message: "ok".to_string(),
+        }))
+    }
+
+    async fn clear_queue(
+        &self,
+        _req: Request<voicev1::Empty>,
+    ) -> std::result::Result<Response<voicev1::CommandResponse>, Status> {
+        let mut q = self.queue.lock().await;
+        q.items.clear();
+        emit_queue_changed(&self.events_tx, &q);
+        self.persist_queue(&q).await;
+
+        Ok(Response::new(voicev1::CommandResponse {
+            ok: true,
+            message: "ok".to_string(),
+        }))
+    }
+
+    async fn next(
+        &self,
+        _req: Request<voicev1::Empty>,
+    ) -> std::result::Result<Response<voicev1::CommandResponse>, Status> {
+        let current = {
+            let st = self.status.lock().await;
+            if st.state == 1 {
+                None
+            } else {
+                Some(QueueItemData {
+                    title: st.now_playing_title.clone(),
+                    source_url: st.now_playing_source_url.clone(),
+                })
+            }
+        };
+
+        let next_item = {
+            let mut q = self.queue.lock().await;
+            if let Some(cur) = current {
+                q.push_history(cur);
+            }
+            let item = q.items.pop_front();
+            emit_queue_changed(&self.events_tx, &q);
+            self.persist_queue(&q).await;
+            item
+        };
+
+        match next_item {
+            Some(item) => {
+                self.start_track(item, true).await;
+            }
+            None => {
+                self.stop_internal().await;
+                let mut st = self.status.lock().await;
+                st.state = 1; // STATE_IDLE
+                st.now_playing_title.clear();
+                st.now_playing_source_url.clear();
+            }
+        }
+
+        Ok(Response::new(voicev1::CommandResponse {
+            ok: true,
+            message: "ok".to_string(),
+        }))
+    }
+
+    async fn previous(
+        &self,
+        _req: Request<voicev1::Empty>,
+    ) -> std::result::Result<Response<voicev1::CommandResponse>, Status> {
+        let current = {
+            let st = self.status.lock().await;
+            if st.state == 1 {
+                None
+            } else {
+                Some(QueueItemData {
+                    title: st.now_playing_title.clone(),
+                    source_url: st.now_playing_source_url.clone(),
+                })
+            }
+        };
+
+        let prev_item = {
+            let mut q = self.queue.lock().await;
+            let prev = q.history.pop_back();
+            if prev.is_some() {
+                if let Some(cur) = current {
+                    q.items.push_front(cur);
+                }
+            }
+            emit_queue_changed(&self.events_tx, &q);
+            self.persist_queue(&q).await;
+            prev
+        };
+
+        match prev_item {
+            Some(item) => {
+                self.start_track(item, true).await;
+                Ok(Response::new(voicev1::CommandResponse {
+                    ok: true,
+                    message: "ok".to_string(),
+                }))
+            }
+            None => Ok(Response::new(voicev1::CommandResponse {
+                ok: false,
+                message: "no previous track".to_string(),
+            })),
+        }
+    }
+
+    async fn set_loop_mode(
+        &self,
+        req: Request<voicev1::SetLoopModeRequest>,
+    ) -> std::result::Result<Response<voicev1::CommandResponse>, Status> {
+        let mode = LoopMode::from_i32(req.into_inner().mode);
+        let mut q = self.queue.lock().await;
+        q.loop_mode = mode;
+        emit_queue_changed(&self.events_tx, &q);
+        self.persist_queue(&q).await;
+
+        Ok(Response::new(voicev1::CommandResponse {
+            ok: true,
+            message: "ok".to_string(),
+        }))
+    }
+
+    async fn send_notice(
+        &self,
+        req: Request<voicev1::NoticeRequest>,
+    ) -> std::result::Result<Response<voicev1::CommandResponse>, Status> {
+        let r = req.into_inner();
+        if !r.message.is_empty() {
+            let mode = if r.target_mode == 3 { 3 } else { 2 };
+            let _ = self.ts3_notice_tx.try_send((mode, r.message));
+        }
+        Ok(Response::new(voicev1::CommandResponse {
+            ok: true,
+            message: "ok".to_string(),
+        }))
+    }
+
+    async fn set_volume(
+        &self,
+        req: Request<voicev1::SetVolumeRequest>,
+    ) -> std::result::Result<Response<voicev1::CommandResponse>, Status> {
+        let v = req.into_inner().volume_percent.clamp(0, 200);
+        {
+            let mut st = self.status.lock().await;
+            st.volume_percent = v;
+        }
+        self.metrics.volume_percent.store(v as i64, std::sync::atomic::Ordering::Relaxed);
+        let q = self.queue.lock().await;
+        self.persist_queue(&q).await;
+
+        Ok(Response::new(voicev1::CommandResponse {
+            ok: true,
+            message: "ok".to_string(),
+        }))
+    }
+
+    async fn get_status(
+        &self,
+        _req: Request<voicev1::Empty>,
+    ) -> std::result::Result<Response<voicev1::StatusResponse>, Status> {
+        let st = self.status.lock().await;
+        Ok(Response::new(voicev1::StatusResponse {
+            state: st.state,
+            now_playing_title: st.now_playing_title.clone(),
+            now_playing_source_url: st.now_playing_source_url.clone(),
+            volume_percent: st.volume_percent,
+            position_ms: self.position_ms.load(std::sync::atomic::Ordering::Relaxed),
+        }))
+    }
+
+    async fn set_audio_fx(
+        &self,
+        req: Request<voicev1::SetAudioFxRequest>,
+    ) -> std::result::Result<Response<voicev1::CommandResponse>, Status> {
+        let r = req.into_inner();
+        {
+            let mut st = self.status.lock().await;
 
             if let Some(p) = r.pan {
                 st.fx_pan = p.clamp(-1.0, 1.0);
@@ -518,10 +1472,9 @@ impl VoiceService for VoiceServiceImpl {
             if let Some(m) = r.reverb_mix {
                 st.fx_reverb_mix = m.clamp(0.0, 1.0);
             }
-
-            PersistedVoiceState::from_status(&st)
-        };
-        let _ = self.persist_tx.try_send(snapshot);
+        }
+        let q = self.queue.lock().await;
+        self.persist_queue(&q).await;
 
         Ok(Response::new(voicev1::CommandResponse {
             ok: true,
@@ -543,6 +1496,398 @@ impl VoiceService for VoiceServiceImpl {
         }))
     }
 
+    async fn set_loudness_config(
+        &self,
+        req: Request<voicev1::SetLoudnessConfigRequest>,
+    ) -> std::result::Result<Response<voicev1::CommandResponse>, Status> {
+        let r = req.into_inner();
+        {
+            let mut st = self.status.lock().await;
+
+            if let Some(e) = r.enabled {
+                st.loudness_enabled = e;
+            }
+            if let Some(t) = r.target_lufs {
+                st.loudness_target_lufs = t.clamp(-40.0, 0.0);
+            }
+            if let Some(m) = r.max_gain_db {
+                st.loudness_max_gain_db = m.clamp(0.0, 24.0);
+            }
+            if let Some(c) = r.limiter_ceiling_db {
+                st.limiter_ceiling_db = c.clamp(-12.0, 0.0);
+            }
+        }
+        let q = self.queue.lock().await;
+        self.persist_queue(&q).await;
+
+        Ok(Response::new(voicev1::CommandResponse {
+            ok: true,
+            message: "ok".to_string(),
+        }))
+    }
+
+    async fn get_loudness_config(
+        &self,
+        _req: Request<voicev1::Empty>,
+    ) -> std::result::Result<Response<voicev1::LoudnessConfigResponse>, Status> {
+        let st = self.status.lock().await;
+        Ok(Response::new(voicev1::LoudnessConfigResponse {
+            enabled: st.loudness_enabled,
+            target_lufs: st.loudness_target_lufs,
+            max_gain_db: st.loudness_max_gain_db,
+            limiter_ceiling_db: st.limiter_ceiling_db,
+        }))
+    }
+
+    async fn set_encoder_config(
+        &self,
+        req: Request<voicev1::SetEncoderConfigRequest>,
+    ) -> std::result::Result<Response<voicev1::CommandResponse>, Status> {
+        let r = req.into_inner();
+        {
+            let mut st = self.status.lock().await;
+
+            if let Some(b) = r.bitrate_bps {
+                st.enc_bitrate_bps = b.max(0);
+            }
+            if let Some(v) = r.vbr {
+                st.enc_vbr = v;
+            }
+            if let Some(f) = r.fec {
+                st.enc_fec = f;
+            }
+            if let Some(p) = r.expected_packet_loss_percent {
+                st.enc_packet_loss_percent = p.clamp(0, 100);
+            }
+            if let Some(c) = r.complexity {
+                st.enc_complexity = c.clamp(0, 10);
+            }
+            if let Some(a) = r.application {
+                st.enc_application = if a == 1 { 1 } else { 2 };
+            }
+            if let Some(c) = r.codec {
+                st.enc_codec = if c == 1 { 1 } else { 2 };
+            }
+            if let Some(d) = r.dtx {
+                st.enc_dtx = d;
+            }
+        }
+        let q = self.queue.lock().await;
+        self.persist_queue(&q).await;
+
+        Ok(Response::new(voicev1::CommandResponse {
+            ok: true,
+            message: "ok".to_string(),
+        }))
+    }
+
+    async fn get_encoder_config(
+        &self,
+        _req: Request<voicev1::Empty>,
+    ) -> std::result::Result<Response<voicev1::EncoderConfigResponse>, Status> {
+        let st = self.status.lock().await;
+        Ok(Response::new(voicev1::EncoderConfigResponse {
+            bitrate_bps: st.enc_bitrate_bps,
+            vbr: st.enc_vbr,
+            fec: st.enc_fec,
+            expected_packet_loss_percent: st.enc_packet_loss_percent,
+            complexity: st.enc_complexity,
+            application: st.enc_application,
+            codec: st.enc_codec,
+            dtx: st.enc_dtx,
+        }))
+    }
+
+    async fn push_audio(
+        &self,
+        req: Request<Streaming<voicev1::AudioFrame>>,
+    ) -> std::result::Result<Response<voicev1::CommandResponse>, Status> {
+        let mut stream = req.into_inner();
+
+        let frame_samples_per_channel = 48000 / 50;
+        let channels = 2usize;
+        let frame_bytes = frame_samples_per_channel * channels * 2;
+
+        // Route through the central mixer (see `mixer.rs`) instead of
+        // holding a private `Encoder` and writing `ts3_audio_tx` directly --
+        // that would let an injected stream and music playback race each
+        // other's independently-encoded Opus onto the single outbound TS3
+        // stream. Going through the mixer also means this RPC picks up the
+        // live `enc_*` config the mixer already reapplies every tick.
+        let mixer_source = self.mixer.register("push_audio", 1.0).await;
+
+        let mut decoder = Decoder::new(audiopus::SampleRate::Hz48000, audiopus::Channels::Stereo)
+            .map_err(|e| Status::internal(format!("opus decoder init failed: {e}")))?;
+
+        let mut reverb = SimpleReverb::new();
+        let bass_cutoff_hz: f32 = 150.0;
+        let fs: f32 = 48000.0;
+        let bass_alpha: f32 = (2.0 * std::f32::consts::PI * bass_cutoff_hz)
+            / (fs + 2.0 * std::f32::consts::PI * bass_cutoff_hz);
+        let mut bass_lp_l: f32 = 0.0;
+        let mut bass_lp_r: f32 = 0.0;
+
+        // Same loudness normalization and always-on true-peak limiter as
+        // `playback_loop`'s FX chain, kept for the life of this stream -- a
+        // per-producer instance, not shared with music playback, same as
+        // `reverb`/the bass lowpass state above.
+        let mut loudness = LoudnessNormalizer::new();
+        let mut limiter = TruePeakLimiter::new();
+
+        let mut float_buf = vec![0f32; frame_samples_per_channel * channels];
+        let mut pcm_scratch = vec![0i16; frame_samples_per_channel * channels];
+        let mut frames_in: u64 = 0;
+
+        while let Some(frame) = stream.message().await.map_err(|e| Status::internal(format!("push_audio stream error: {e}")))? {
+            // AudioFrame.encoding: 0=PCM_S16LE_48K_STEREO, 1=OPUS.
+            let pcm_in: &[i16] = if frame.encoding == 1 {
+                match decoder.decode(Some(&frame.data), &mut pcm_scratch, false) {
+                    Ok(samples_per_channel) => {
+                        let n = (samples_per_channel * channels).min(pcm_scratch.len());
+                        &pcm_scratch[..n]
+                    }
+                    Err(e) => {
+                        warn!(%e, "push_audio: opus decode failed");
+                        continue;
+                    }
+                }
+            } else {
+                if frame.data.len() != frame_bytes {
+                    warn!(len = %frame.data.len(), expected = %frame_bytes, "push_audio: dropping PCM frame with unexpected size");
+                    continue;
+                }
+                for i in 0..(frame_samples_per_channel * channels) {
+                    let lo = frame.data[i * 2];
+                    let hi = frame.data[i * 2 + 1];
+                    pcm_scratch[i] = i16::from_le_bytes([lo, hi]);
+                }
+                &pcm_scratch[..]
+            };
+
+            let (
+                vol,
+                fx_pan,
+                fx_width,
+                fx_swap_lr,
+                fx_bass_db,
+                fx_reverb_mix,
+                loudness_enabled,
+                loudness_target_lufs,
+                loudness_max_gain_db,
+                limiter_ceiling_db,
+            ) = {
+                let st = self.status.lock().await;
+                let r = (st.volume_percent as f32 / 100.0).clamp(0.0, 2.0);
+                let vol = if r <= 1.0 { r.powf(1.6) } else { r };
+                (
+                    vol,
+                    st.fx_pan.clamp(-1.0, 1.0),
+                    st.fx_width.clamp(0.0, 3.0),
+                    st.fx_swap_lr,
+                    st.fx_bass_db.clamp(0.0, 18.0),
+                    st.fx_reverb_mix.clamp(0.0, 1.0),
+                    st.loudness_enabled,
+                    st.loudness_target_lufs.clamp(-40.0, 0.0),
+                    st.loudness_max_gain_db.clamp(0.0, 24.0),
+                    st.limiter_ceiling_db.clamp(-12.0, 0.0),
+                )
+            };
+
+            // Opus frames aren't always 20ms (2.5/5/10/40/60ms are all
+            // legal), so a decoded frame can come up short of the fixed 20ms
+            // scratch buffers below -- process only what actually decoded
+            // instead of the fixed frame size, or this indexes out of bounds
+            // on `pcm_in` for a valid short frame.
+            let samples_per_ch = pcm_in.len() / channels;
+
+            for i in 0..(samples_per_ch * channels) {
+                float_buf[i] = (pcm_in[i] as f32 / 32768.0) * vol;
+            }
+
+            if loudness_enabled {
+                for i in 0..samples_per_ch {
+                    let idx = i * 2;
+                    let mut l = float_buf[idx];
+                    let mut r = float_buf[idx + 1];
+                    loudness.measure(l, r);
+                    loudness.apply_gain(&mut l, &mut r, loudness_target_lufs, loudness_max_gain_db);
+                    float_buf[idx] = l;
+                    float_buf[idx + 1] = r;
+                }
+            }
+
+            let bass_gain = 10.0_f32.powf(fx_bass_db / 20.0);
+            for i in 0..samples_per_ch {
+                let idx = i * 2;
+                let mut l = float_buf[idx];
+                let mut r = float_buf[idx + 1];
+
+                if (bass_gain - 1.0).abs() > 0.0001 {
+                    bass_lp_l += bass_alpha * (l - bass_lp_l);
+                    bass_lp_r += bass_alpha * (r - bass_lp_r);
+                    l = (l - bass_lp_l) + bass_lp_l * bass_gain;
+                    r = (r - bass_lp_r) + bass_lp_r * bass_gain;
+                }
+
+                let (l2, r2) = reverb.process_stereo(l, r, fx_reverb_mix);
+                l = l2;
+                r = r2;
+
+                if fx_swap_lr {
+                    std::mem::swap(&mut l, &mut r);
+                }
+                if (fx_width - 1.0).abs() > 0.0001 {
+                    let m = 0.5 * (l + r);
+                    let s = 0.5 * (l - r) * fx_width;
+                    l = m + s;
+                    r = m - s;
+                }
+                let (lg, rg) = if fx_pan >= 0.0 {
+                    ((1.0 - fx_pan).clamp(0.0, 1.0), 1.0)
+                } else {
+                    (1.0, (1.0 + fx_pan).clamp(0.0, 1.0))
+                };
+                float_buf[idx] = l * lg;
+                float_buf[idx + 1] = r * rg;
+            }
+
+            let limiter_ceiling = 10.0_f32.powf(limiter_ceiling_db / 20.0);
+            for i in 0..samples_per_ch {
+                let idx = i * 2;
+                let (l, r) = limiter.process(float_buf[idx], float_buf[idx + 1], limiter_ceiling);
+                float_buf[idx] = l;
+                float_buf[idx + 1] = r;
+            }
+
+            let out_frame: Vec<i16> = float_buf[..samples_per_ch * channels]
+                .iter()
+                .map(|&v| (v.clamp(-1.0, 1.0) * 32767.0) as i16)
+                .collect();
+            if mixer_source.send(out_frame).await.is_err() {
+                return Err(Status::internal("mixer source closed"));
+            }
+            frames_in += 1;
+        }
+
+        Ok(Response::new(voicev1::CommandResponse {
+            ok: true,
+            message: format!("received {frames_in} frames"),
+        }))
+    }
+
+    async fn push_ssrc_audio(
+        &self,
+        req: Request<Streaming<voicev1::SsrcAudioFrame>>,
+    ) -> std::result::Result<Response<voicev1::CommandResponse>, Status> {
+        let Some(ingest) = self.discord_ingest.clone() else {
+            return Ok(Response::new(voicev1::CommandResponse {
+                ok: false,
+                message: "discord ingest disabled; set TSBOT_DISCORD_INGEST_ENABLE=1".to_string(),
+            }));
+        };
+
+        let mut stream = req.into_inner();
+
+        let frame_samples_per_channel = 48000 / 50;
+        let channels = 2usize;
+        let frame_bytes = frame_samples_per_channel * channels * 2;
+
+        let mut decoders: HashMap<u32, Decoder> = HashMap::new();
+        let mut pcm_scratch = vec![0i16; frame_samples_per_channel * channels];
+        let mut frames_in: u64 = 0;
+
+        while let Some(frame) = stream
+            .message()
+            .await
+            .map_err(|e| Status::internal(format!("push_ssrc_audio stream error: {e}")))?
+        {
+            // AudioFrame.encoding: 0=PCM_S16LE_48K_STEREO, 1=OPUS.
+            if frame.encoding == 1 {
+                let decoder = decoders.entry(frame.ssrc).or_insert_with(|| {
+                    Decoder::new(audiopus::SampleRate::Hz48000, audiopus::Channels::Stereo)
+                        .expect("opus decoder init")
+                });
+                match decoder.decode(Some(&frame.data), &mut pcm_scratch, false) {
+                    Ok(samples_per_channel) => {
+                        let n = (samples_per_channel * channels).min(pcm_scratch.len());
+                        ingest.push_ssrc_frame(frame.ssrc, &pcm_scratch[..n]).await;
+                        frames_in += 1;
+                    }
+                    Err(e) => {
+                        warn!(ssrc = frame.ssrc, %e, "push_ssrc_audio: opus decode failed");
+                    }
+                }
+                continue;
+            }
+
+            if frame.data.len() != frame_bytes {
+                warn!(ssrc = frame.ssrc, len = %frame.data.len(), expected = %frame_bytes, "push_ssrc_audio: dropping PCM frame with unexpected size");
+                continue;
+            }
+
+            let pcm: Vec<i16> = frame
+                .data
+                .chunks_exact(2)
+                .map(|b| i16::from_le_bytes([b[0], b[1]]))
+                .collect();
+            ingest.push_ssrc_frame(frame.ssrc, &pcm).await;
+            frames_in += 1;
+        }
+
+        Ok(Response::new(voicev1::CommandResponse {
+            ok: true,
+            message: format!("received {frames_in} ssrc frames"),
+        }))
+    }
+
+    async fn start_recording(
+        &self,
+        req: Request<voicev1::StartRecordingRequest>,
+    ) -> std::result::Result<Response<voicev1::CommandResponse>, Status> {
+        let req = req.into_inner();
+        if req.path.trim().is_empty() {
+            return Ok(Response::new(voicev1::CommandResponse {
+                ok: false,
+                message: "path must not be empty".to_string(),
+            }));
+        }
+        let path = resolve_repo_relative(&req.path);
+
+        if self
+            .ts3_record_tx
+            .send(RecordCommand::Start(path, req.per_speaker))
+            .await
+            .is_err()
+        {
+            return Ok(Response::new(voicev1::CommandResponse {
+                ok: false,
+                message: "ts3 actor unavailable".to_string(),
+            }));
+        }
+
+        Ok(Response::new(voicev1::CommandResponse {
+            ok: true,
+            message: "accepted".to_string(),
+        }))
+    }
+
+    async fn stop_recording(
+        &self,
+        _req: Request<voicev1::Empty>,
+    ) -> std::result::Result<Response<voicev1::CommandResponse>, Status> {
+        if self.ts3_record_tx.send(RecordCommand::Stop).await.is_err() {
+            return Ok(Response::new(voicev1::CommandResponse {
+                ok: false,
+                message: "ts3 actor unavailable".to_string(),
+            }));
+        }
+
+        Ok(Response::new(voicev1::CommandResponse {
+            ok: true,
+            message: "ok".to_string(),
+        }))
+    }
+
     async fn subscribe_events(
         &self,
         req: Request<voicev1::SubscribeRequest>,
@@ -553,6 +1898,10 @@ impl VoiceService for VoiceServiceImpl {
             let include_chat = cfg.include_chat;
             let include_playback = cfg.include_playback;
             let include_log = cfg.include_log;
+            let include_queue = cfg.include_queue;
+            let include_voice_activity = cfg.include_voice_activity;
+            let include_metadata = cfg.include_metadata;
+            let include_progress = cfg.include_progress;
             async move {
                 match r {
                     Ok(ev) => {
@@ -560,6 +1909,10 @@ impl VoiceService for VoiceServiceImpl {
                             Some(voicev1::event::Payload::Chat(_)) => include_chat,
                             Some(voicev1::event::Payload::Playback(_)) => include_playback,
                             Some(voicev1::event::Payload::Log(_)) => include_log,
+                            Some(voicev1::event::Payload::Queue(_)) => include_queue,
+                            Some(voicev1::event::Payload::VoiceActivity(_)) => include_voice_activity,
+                            Some(voicev1::event::Payload::Metadata(_)) => include_metadata,
+                            Some(voicev1::event::Payload::Progress(_)) => include_progress,
                             None => false,
                         };
                         if ok { Some(Ok(ev)) } else { None }
@@ -571,19 +1924,168 @@ impl VoiceService for VoiceServiceImpl {
         Ok(Response::new(Box::pin(stream) as Self::SubscribeEventsStream))
     }
 
-    type SubscribeEventsStream = std::pin::Pin<Box<dyn tokio_stream::Stream<Item = std::result::Result<voicev1::Event, Status>> + Send>>;
-}
+    type SubscribeEventsStream = std::pin::Pin<Box<dyn tokio_stream::Stream<Item = std::result::Result<voicev1::Event, Status>> + Send>>;
+}
+
+impl VoiceServiceImpl {
+    async fn stop_internal(&self) {
+        let mut pb = self.playback.lock().await;
+        if let Some(p) = pb.take() {
+            p.cancel.cancel();
+            let abort_handle = p.handle.abort_handle();
+            let join = p.handle;
+            let r = tokio::time::timeout(Duration::from_secs(2), join).await;
+            if r.is_err() {
+                abort_handle.abort();
+            }
+        }
+        self.position_ms.store(0, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    async fn persist_queue(&self, q: &QueueState) {
+        let snapshot = {
+            let st = self.status.lock().await;
+            PersistedVoiceState::from_status(&st).with_queue(q)
+        };
+        let _ = self.persist_tx.try_send(snapshot);
+    }
+
+    /// Start playing `item`. When `stop_current` is true, any currently
+    /// running `playback_loop` is cancelled and joined first (used by
+    /// explicit `Play`/`Next`/`Previous`); when false, the caller is the
+    /// just-finished playback task itself, which is about to exit on its
+    /// own, so we simply install the new `PlaybackControl`.
+    async fn start_track(&self, item: QueueItemData, stop_current: bool) {
+        // Unconditionally, not just when `stop_current` cancels the old
+        // `playback_loop`'s own cancel tree -- a track that just played to
+        // its natural end never had `stop_internal()` called on it, so its
+        // metadata task would otherwise keep running (and broadcasting
+        // stale `ProgressEvent`s) forever. See `track_meta_cancel`'s doc.
+        if let Some(prev) = self.track_meta_cancel.lock().await.take() {
+            prev.cancel();
+        }
+
+        {
+            let mut st = self.status.lock().await;
+            st.now_playing_title = item.title.clone();
+            st.now_playing_source_url = item.source_url.clone();
+            st.state = 2; // STATE_PLAYING
+        }
+
+        // PlaybackEvent.Type: STARTED=1
+        emit_playback(&self.events_tx, 1, item.title.clone(), item.source_url.clone(), "");
+        self.metrics.record_playback_event(1);
+
+        if stop_current {
+            self.stop_internal().await;
+        }
+
+        let (paused_tx, paused_rx) = watch::channel(false);
+        let (seek_tx, seek_rx) = watch::channel(None);
+        let cancel = CancellationToken::new();
+        let cancel_child = cancel.clone();
+
+        self.position_ms.store(0, std::sync::atomic::Ordering::Relaxed);
+        self.buffer_occupancy.store(0, std::sync::atomic::Ordering::Relaxed);
+
+        let mixer_source = self.mixer.register("music_playback", 1.0).await;
+        let this = self.clone();
+        let position_ms = self.position_ms.clone();
+        let buffer_occupancy = self.buffer_occupancy.clone();
+
+        let handle = tokio::spawn(async move {
+            let this_loop = this.clone();
+            let (last_item, outcome) = playback_loop(
+                this_loop,
+                item,
+                mixer_source,
+                paused_rx,
+                seek_rx,
+                cancel_child,
+                position_ms,
+                buffer_occupancy,
+            )
+            .await;
+            match outcome {
+                PlaybackStop::Finished(Ok(())) => {
+                    // PlaybackEvent.Type: FINISHED=2
+                    emit_playback(&this.events_tx, 2, last_item.title.clone(), last_item.source_url.clone(), "");
+                    this.metrics.record_playback_event(2);
+                    this.advance_queue_after_finish(last_item).await;
+                }
+                PlaybackStop::Finished(Err(e)) => {
+                    error!(%e, "playback loop failed");
+                    // PlaybackEvent.Type: ERROR=3
+                    emit_playback(&this.events_tx, 3, last_item.title.clone(), last_item.source_url.clone(), format!("{e}"));
+                    this.metrics.record_playback_event(3);
+                    this.advance_queue_after_finish(last_item).await;
+                }
+                PlaybackStop::Cancelled => {
+                    // `stop_internal()` cancelled us -- either a plain Stop
+                    // (nothing should start next) or `start_track`'s
+                    // `stop_current` branch ahead of Next/Previous/Skip
+                    // (which already popped the queue itself). Either way
+                    // the caller that cancelled us owns what plays next; if
+                    // we also called `advance_queue_after_finish` here we'd
+                    // double-pop the queue and leave this now-orphaned
+                    // mixer source's replacement dangling.
+                }
+            }
+        });
+
+        let mut pb = self.playback.lock().await;
+        *pb = Some(PlaybackControl {
+            cancel,
+            paused_tx,
+            seek_tx,
+            handle,
+        });
+    }
+
+    /// Pops whatever plays after `just_finished` (respecting loop mode),
+    /// updates history, and broadcasts/persists the resulting queue state.
+    /// Shared by the normal end-of-track path below and by `playback_loop`'s
+    /// internal crossfade transition, which commits a queue advance without
+    /// spawning a fresh task.
+    async fn pop_next_for_queue(&self, just_finished: QueueItemData) -> Option<QueueItemData> {
+        let mut q = self.queue.lock().await;
+        let next = q.take_next_on_finish(&just_finished);
+        if q.loop_mode != LoopMode::Track {
+            q.push_history(just_finished);
+        }
+        emit_queue_changed(&self.events_tx, &q);
+        self.persist_queue(&q).await;
+        next
+    }
+
+    /// Called from inside the just-finished `playback_loop` task (success or
+    /// failure alike) to pop/advance the queue and start the next item,
+    /// respecting loop mode. The queue pop happens inline; the next item is
+    /// started in a freshly spawned task so that never blocks the task it
+    /// was called from.
+    async fn advance_queue_after_finish(&self, just_finished: QueueItemData) {
+        match self.pop_next_for_queue(just_finished).await {
+            Some(item) => {
+                let this = self.clone();
+                tokio::spawn(async move {
+                    this.start_track(item, false).await;
+                });
+            }
+            None => {
+                // No next track means `start_track` (which otherwise does
+                // this unconditionally) never runs again, so the just-
+                // finished track's metadata task would leak here too.
+                if let Some(prev) = self.track_meta_cancel.lock().await.take() {
+                    prev.cancel();
+                }
+                self.position_ms.store(0, std::sync::atomic::Ordering::Relaxed);
 
-impl VoiceServiceImpl {
-    async fn stop_internal(&self) {
-        let mut pb = self.playback.lock().await;
-        if let Some(p) = pb.take() {
-            p.cancel.cancel();
-            let abort_handle = p.handle.abort_handle();
-            let join = p.handle;
-            let r = tokio::time::timeout(Duration::from_secs(2), join).await;
-            if r.is_err() {
-                abort_handle.abort();
+                let mut st = self.status.lock().await;
+                if st.state == 2 {
+                    st.state = 1; // STATE_IDLE
+                    st.now_playing_title.clear();
+                    st.now_playing_source_url.clear();
+                }
             }
         }
     }
@@ -630,8 +2132,11 @@ async fn ts3_actor(
     mut audio_rx: mpsc::Receiver<OutPacket>,
     mut notice_rx: mpsc::Receiver<(i32, String)>,
     mut cmd_rx: mpsc::Receiver<OutCommand>,
+    mut record_rx: mpsc::Receiver<RecordCommand>,
     events_tx: broadcast::Sender<voicev1::Event>,
     shutdown_token: CancellationToken,
+    buffer_occupancy: Arc<std::sync::atomic::AtomicUsize>,
+    metrics: Arc<metrics::Metrics>,
 ) -> Result<()> {
     let host = get_env("TSBOT_TS3_HOST", "127.0.0.1");
     let port = get_env("TSBOT_TS3_PORT", "9987");
@@ -713,6 +2218,14 @@ async fn ts3_actor(
     let mut avatar_set_done = false;
     let mut backoff = Duration::from_secs(1);
     let max_backoff = Duration::from_secs(60);
+    let mut ever_connected = false;
+
+    // Inbound-voice state outlives individual reconnects: a recording spans
+    // the whole StartRecording/StopRecording window regardless of TS3
+    // connection hiccups in between.
+    let mut speakers: HashMap<ClientId, SpeakerState> = HashMap::new();
+    let mut recording: Option<RecordingMode> = None;
+    let mut pcm_scratch: [i16; OPUS_DECODE_SCRATCH] = [0; OPUS_DECODE_SCRATCH];
 
     'outer: loop {
         if shutdown_token.is_cancelled() {
@@ -739,6 +2252,11 @@ async fn ts3_actor(
             Ok(c) => {
                 backoff = Duration::from_secs(1);
                 out_buf.clear();
+                metrics.connection_up.store(1, std::sync::atomic::Ordering::Relaxed);
+                if ever_connected {
+                    metrics.reconnects.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                }
+                ever_connected = true;
                 c
             }
             Err(e) => {
@@ -772,6 +2290,7 @@ async fn ts3_actor(
 
         let mut event_tick = tokio::time::interval(std::time::Duration::from_millis(50));
         let mut send_tick = tokio::time::interval(std::time::Duration::from_millis(20));
+        let mut record_tick = tokio::time::interval(std::time::Duration::from_millis(20));
 
         'inner: loop {
             tokio::select! {
@@ -938,6 +2457,43 @@ async fn ts3_actor(
                                 }
                             }
 
+                            Some(Some(Ok(StreamItem::Audio(packet)))) => {
+                                // Decoding Opus every packet is wasted CPU when nobody is
+                                // listening; only do it while a subscriber or a recording
+                                // actually needs the PCM.
+                                let want_audio = recording.is_some() || events_tx.receiver_count() > 0;
+                                if want_audio {
+                                    if let AudioData::S2C { from, data, .. } = packet.data() {
+                                        let speaker = speakers.entry(*from).or_insert_with(|| SpeakerState {
+                                            decoder: Decoder::new(audiopus::SampleRate::Hz48000, audiopus::Channels::Stereo)
+                                                .expect("opus decoder init"),
+                                            ring: VecDeque::new(),
+                                            last_packet: Instant::now(),
+                                        });
+                                        speaker.last_packet = Instant::now();
+
+                                        match speaker.decoder.decode(Some(data), &mut pcm_scratch, false) {
+                                            Ok(samples_per_channel) => {
+                                                // `decode`'s return is per-channel; the interleaved
+                                                // stereo buffer it filled is twice that long.
+                                                let n = (samples_per_channel * 2).min(pcm_scratch.len());
+                                                speaker.ring.extend(pcm_scratch[..n].iter().copied());
+                                                let _ = events_tx.send(voicev1::Event {
+                                                    unix_ms: now_unix_ms(),
+                                                    payload: Some(voicev1::event::Payload::VoiceActivity(voicev1::VoiceActivityEvent {
+                                                        client_id: from.0 as i32,
+                                                        rms_level: rms_i16(&pcm_scratch[..n]),
+                                                    })),
+                                                });
+                                            }
+                                            Err(e) => {
+                                                emit_log(&events_tx, 3, format!("opus decode failed for client {}: {e}", from.0));
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+
                             Some(Some(Ok(_))) => {}
 
                             Some(Some(Err(e))) => {
@@ -995,12 +2551,17 @@ async fn ts3_actor(
 
                     if now >= diag_next {
                         diag_next = now + Duration::from_secs(5);
+                        let pcm_buffered = buffer_occupancy.load(std::sync::atomic::Ordering::Relaxed);
                         let msg = format!(
-                            "audio_send_diag: out_buf_max={} drops={} send_jitter_max_ms={} send_audio_errs={}",
-                            out_buf_max, out_buf_drops, send_jitter_max_ms, send_audio_errs
+                            "audio_send_diag: out_buf_max={} drops={} send_jitter_max_ms={} send_audio_errs={} pcm_buffered={}",
+                            out_buf_max, out_buf_drops, send_jitter_max_ms, send_audio_errs, pcm_buffered
                         );
                         emit_log(&events_tx, 2, msg.clone());
                         info!("{msg}");
+                        metrics.send_jitter_max_ms.store(send_jitter_max_ms as u64, std::sync::atomic::Ordering::Relaxed);
+                        metrics.out_buf_max.store(out_buf_max as u64, std::sync::atomic::Ordering::Relaxed);
+                        metrics.out_buf_drops.store(out_buf_drops, std::sync::atomic::Ordering::Relaxed);
+                        metrics.send_audio_errs.store(send_audio_errs, std::sync::atomic::Ordering::Relaxed);
                         out_buf_max = out_buf.len();
                         send_jitter_max_ms = 0;
                         send_audio_errs = 0;
@@ -1048,9 +2609,121 @@ async fn ts3_actor(
                         break 'outer;
                     }
                 }
+
+                cmd = record_rx.recv() => {
+                    match cmd {
+                        Some(RecordCommand::Start(path, per_speaker)) => {
+                            let mode = if per_speaker {
+                                Some(RecordingMode::PerSpeaker {
+                                    base_path: path.clone(),
+                                    writers: HashMap::new(),
+                                })
+                            } else {
+                                match RecordingState::create(path.clone()) {
+                                    Ok(rs) => Some(RecordingMode::Mixed(rs)),
+                                    Err(e) => {
+                                        emit_log(&events_tx, 3, format!("failed to start recording: {e}"));
+                                        None
+                                    }
+                                }
+                            };
+                            if let Some(mode) = mode {
+                                recording = Some(mode);
+                                emit_log(&events_tx, 2, format!("recording started: {}", path.display()));
+                            }
+                        }
+                        Some(RecordCommand::Stop) => {
+                            if let Some(rm) = recording.take() {
+                                match rm.finalize() {
+                                    Ok(()) => emit_log(&events_tx, 2, "recording stopped".to_string()),
+                                    Err(e) => emit_log(&events_tx, 3, format!("failed to finalize recording: {e}")),
+                                }
+                            }
+                        }
+                        None => {
+                            break 'outer;
+                        }
+                    }
+                }
+
+                _ = record_tick.tick() => {
+                    let stale: Vec<ClientId> = speakers
+                        .iter()
+                        .filter(|(_, s)| s.last_packet.elapsed() >= SPEAKER_SILENCE_TIMEOUT)
+                        .map(|(id, _)| *id)
+                        .collect();
+                    for id in &stale {
+                        speakers.remove(id);
+                        if let Some(RecordingMode::PerSpeaker { writers, .. }) = recording.as_mut() {
+                            if let Some(rs) = writers.remove(id) {
+                                if let Err(e) = rs.finalize() {
+                                    emit_log(&events_tx, 3, format!("failed to finalize per-speaker recording: {e}"));
+                                }
+                            }
+                        }
+                    }
+
+                    match recording.as_mut() {
+                        Some(RecordingMode::Mixed(rs)) => {
+                            let mut accum = [0i32; RECORD_FRAME_SAMPLES];
+                            let mut any = false;
+                            for speaker in speakers.values_mut() {
+                                if speaker.ring.len() < RECORD_FRAME_SAMPLES {
+                                    continue;
+                                }
+                                any = true;
+                                for slot in accum.iter_mut() {
+                                    *slot += speaker.ring.pop_front().unwrap_or(0) as i32;
+                                }
+                            }
+                            if any {
+                                let frame: Vec<i16> = accum
+                                    .iter()
+                                    .map(|&s| s.clamp(i16::MIN as i32, i16::MAX as i32) as i16)
+                                    .collect();
+                                if let Err(e) = rs.write_frame(&frame) {
+                                    emit_log(&events_tx, 3, format!("recording write failed: {e}"));
+                                    recording = None;
+                                }
+                            }
+                        }
+                        Some(RecordingMode::PerSpeaker { base_path, writers }) => {
+                            for (client_id, speaker) in speakers.iter_mut() {
+                                if speaker.ring.len() < RECORD_FRAME_SAMPLES {
+                                    continue;
+                                }
+                                let frame: Vec<i16> = (0..RECORD_FRAME_SAMPLES)
+                                    .map(|_| speaker.ring.pop_front().unwrap_or(0))
+                                    .collect();
+
+                                if !writers.contains_key(client_id) {
+                                    match RecordingState::create(per_speaker_path(base_path, client_id.0)) {
+                                        Ok(rs) => {
+                                            writers.insert(*client_id, rs);
+                                        }
+                                        Err(e) => {
+                                            emit_log(&events_tx, 3, format!("failed to start per-speaker recording for client {}: {e}", client_id.0));
+                                            continue;
+                                        }
+                                    }
+                                }
+
+                                if let Some(rs) = writers.get_mut(client_id) {
+                                    if let Err(e) = rs.write_frame(&frame) {
+                                        emit_log(&events_tx, 3, format!("per-speaker recording write failed: {e}"));
+                                        writers.remove(client_id);
+                                    }
+                                }
+                            }
+                        }
+                        None => {}
+                    }
+                }
             }
         }
 
+        metrics.connection_up.store(0, std::sync::atomic::Ordering::Relaxed);
+
         if send_audio_errs > 0 {
             emit_log(
                 &events_tx,
@@ -1093,18 +2766,167 @@ async fn ts3_actor(
     Ok(())
 }
 
-async fn playback_loop(
+/// Result of [`probe_track_metadata`], broadcast as a `TrackMetadataEvent`.
+/// `title`/`artist`/`album` are blank and `duration_ms`/`bitrate_kbps` are 0
+/// when ffprobe has nothing to report for that field.
+struct TrackMetadata {
+    title: String,
+    artist: String,
+    album: String,
+    duration_ms: i64,
+    bitrate_kbps: i32,
+}
+
+#[derive(Deserialize, Default)]
+struct FfprobeFormatTags {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    artist: String,
+    #[serde(default)]
+    album: String,
+}
+
+#[derive(Deserialize, Default)]
+struct FfprobeFormat {
+    #[serde(default)]
+    duration: Option<String>,
+    #[serde(default)]
+    bit_rate: Option<String>,
+    #[serde(default)]
+    tags: FfprobeFormatTags,
+}
+
+#[derive(Deserialize, Default)]
+struct FfprobeOutput {
+    #[serde(default)]
+    format: FfprobeFormat,
+}
+
+/// Probes `source_url`'s container-level tags and format info via `ffprobe`,
+/// falling back to `fallback_title` (the queue item's own title) when the
+/// source has no `title` tag of its own. `None` only when ffprobe itself
+/// can't be run or its JSON doesn't parse -- a source with no tags at all
+/// still yields `Some` with blank/zero fields, distinct from a failed probe.
+async fn probe_track_metadata(source_url: &str, fallback_title: &str) -> Option<TrackMetadata> {
+    let out = tokio::process::Command::new("ffprobe")
+        .arg("-v")
+        .arg("error")
+        .arg("-print_format")
+        .arg("json")
+        .arg("-show_entries")
+        .arg("format=duration,bit_rate:format_tags=title,artist,album")
+        .arg(source_url)
+        .output()
+        .await
+        .ok()?;
+    let parsed: FfprobeOutput = serde_json::from_slice(&out.stdout).ok()?;
+    let format = parsed.format;
+    let title = if format.tags.title.is_empty() {
+        fallback_title.to_string()
+    } else {
+        format.tags.title
+    };
+    let duration_ms = format
+        .duration
+        .and_then(|d| d.parse::<f64>().ok())
+        .map(|secs| (secs * 1000.0) as i64)
+        .unwrap_or(0);
+    let bitrate_kbps = format
+        .bit_rate
+        .and_then(|b| b.parse::<i64>().ok())
+        .map(|bps| (bps / 1000) as i32)
+        .unwrap_or(0);
+    Some(TrackMetadata {
+        title,
+        artist: format.tags.artist,
+        album: format.tags.album,
+        duration_ms,
+        bitrate_kbps,
+    })
+}
+
+/// Spawned once per track alongside its `playback_loop`, independent of that
+/// loop's tight 20ms tick cadence so a slow ffprobe can never stall audio.
+/// Probes and emits the track's `TrackMetadataEvent` once, then emits a
+/// `ProgressEvent` every couple of seconds off the same `position_ms` the
+/// mixer pipeline already maintains, until `cancel` fires (track finishes,
+/// is skipped, or playback stops). `test:` sources have nothing for ffprobe
+/// to read, so they're skipped entirely rather than probed for nothing.
+fn spawn_track_metadata_task(
+    events_tx: broadcast::Sender<voicev1::Event>,
     source_url: String,
-    ts3_audio_tx: mpsc::Sender<OutPacket>,
-    mut paused_rx: watch::Receiver<bool>,
+    title: String,
+    position_ms: Arc<std::sync::atomic::AtomicI64>,
     cancel: CancellationToken,
-    status: Arc<Mutex<SharedStatus>>,
-) -> Result<()> {
-    let playback_started = Instant::now();
-    info!(source_url = %source_url, "playback starting");
+) {
+    if source_url.starts_with("test:") {
+        return;
+    }
+    tokio::spawn(async move {
+        let duration_ms = match probe_track_metadata(&source_url, &title).await {
+            Some(meta) => {
+                let duration_ms = meta.duration_ms;
+                emit_metadata(&events_tx, meta);
+                duration_ms
+            }
+            None => 0,
+        };
+
+        let mut tick = tokio::time::interval(Duration::from_secs(2));
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = tick.tick() => {
+                    let position_ms = position_ms.load(std::sync::atomic::Ordering::Relaxed);
+                    emit_progress(&events_tx, position_ms, duration_ms);
+                }
+            }
+        }
+    });
+}
+
+/// Probes `source_url`'s first audio stream's sample rate via `ffprobe`.
+/// `None` on anything that doesn't parse cleanly (missing ffprobe, no audio
+/// stream, unexpected output) -- callers fall back to assuming 48kHz, same
+/// as the pre-resampler behaviour.
+async fn probe_native_samplerate(source_url: &str) -> Option<u32> {
+    let out = tokio::process::Command::new("ffprobe")
+        .arg("-v")
+        .arg("error")
+        .arg("-select_streams")
+        .arg("a:0")
+        .arg("-show_entries")
+        .arg("stream=sample_rate")
+        .arg("-of")
+        .arg("csv=p=0")
+        .arg(source_url)
+        .output()
+        .await
+        .ok()?;
+    String::from_utf8_lossy(&out.stdout).trim().parse().ok()
+}
+
+/// Launches ffmpeg decoding `source_url` to raw stereo s16le PCM at
+/// `min(native_rate, max_samplerate)` -- so a source well above the cap
+/// isn't decoded at full resolution for nothing -- starting
+/// `start_offset_secs` into the stream, and spawns the reader task that
+/// resamples (see [`crate::resampler`]) up/down to the pipeline's fixed
+/// 48kHz and feeds `frame_bytes`-sized 48kHz frames into the returned
+/// channel. Used both for the initial decode and to relaunch the decoder
+/// after a `Seek`.
+async fn spawn_ffmpeg_decoder(
+    source_url: &str,
+    start_offset_secs: f64,
+    frame_bytes: usize,
+    max_samplerate: u32,
+    cancel: CancellationToken,
+) -> Result<(ChildKillOnDrop, mpsc::Receiver<Vec<u8>>)> {
+    let native_rate = probe_native_samplerate(source_url).await.unwrap_or(48000);
+    let decode_rate = native_rate.min(max_samplerate).max(8000);
 
-    let child = tokio::process::Command::new("ffmpeg")
-        .arg("-nostdin")
+    let mut cmd = tokio::process::Command::new("ffmpeg");
+    cmd.arg("-nostdin")
         .arg("-loglevel")
         .arg("error")
         .arg("-reconnect")
@@ -1114,13 +2936,19 @@ async fn playback_loop(
         .arg("-reconnect_delay_max")
         .arg("5")
         .arg("-rw_timeout")
-        .arg("15000000")
+        .arg("15000000");
+
+    if start_offset_secs > 0.0 {
+        cmd.arg("-ss").arg(format!("{:.3}", start_offset_secs));
+    }
+
+    let child = cmd
         .arg("-i")
-        .arg(&source_url)
+        .arg(source_url)
         .arg("-f")
         .arg("s16le")
         .arg("-ar")
-        .arg("48000")
+        .arg(decode_rate.to_string())
         .arg("-ac")
         .arg("2")
         .arg("pipe:1")
@@ -1132,7 +2960,7 @@ async fn playback_loop(
     let mut child = ChildKillOnDrop::new(child);
 
     if let Some(stderr) = child.child_mut().stderr.take() {
-        let src = source_url.clone();
+        let src = source_url.to_string();
         tokio::spawn(async move {
             let mut lines = BufReader::new(stderr).lines();
             while let Ok(Some(line)) = lines.next_line().await {
@@ -1149,57 +2977,177 @@ async fn playback_loop(
 
     // Encode/send loop must keep a stable 20ms cadence to prevent TS3 jitter buffer underruns.
     // We decouple ffmpeg reads from the send cadence via a small PCM frame queue.
-    let (pcm_tx, mut pcm_rx) = mpsc::channel::<Vec<u8>>(50);
-
-    let encoder = Encoder::new(
-        audiopus::SampleRate::Hz48000,
-        audiopus::Channels::Stereo,
-        audiopus::Application::Audio,
-    )
-    .map_err(|e| anyhow!("opus encoder init failed: {e}"))?;
+    let (pcm_tx, pcm_rx) = mpsc::channel::<Vec<u8>>(50);
 
-    let frame_samples_per_channel = 48000 / 50;
     let channels = 2usize;
-    let bytes_per_sample = 2usize;
-    let frame_bytes = frame_samples_per_channel * channels * bytes_per_sample;
-    let frame_duration = Duration::from_millis(20);
+    // 20ms worth of native-rate PCM per read; resampled and re-chunked into
+    // `frame_bytes`-sized (48kHz) frames below regardless of how that
+    // divides, so a non-round decode_rate just costs a little extra
+    // buffering latency rather than breaking the cadence.
+    let native_frame_bytes = (decode_rate as usize / 50) * channels * 2;
+    let frame_samples_total = frame_bytes / 2;
 
-    let mut pcm = vec![0u8; frame_bytes];
-    let mut float_buf = vec![0f32; frame_samples_per_channel * channels];
-    let mut opus_out = [0u8; 1275];
-
-    let mut reverb = SimpleReverb::new();
-    let bass_cutoff_hz: f32 = 150.0;
-    let fs: f32 = 48000.0;
-    let bass_alpha: f32 = (2.0 * std::f32::consts::PI * bass_cutoff_hz)
-        / (fs + 2.0 * std::f32::consts::PI * bass_cutoff_hz);
-    let mut bass_lp_l: f32 = 0.0;
-    let mut bass_lp_r: f32 = 0.0;
-
-    // Reader task: continuously read PCM frames from ffmpeg.
-    // On EOF or error, it will stop sending and close the channel.
     let reader_cancel = cancel.clone();
-    let reader_src = source_url.clone();
+    let reader_src = source_url.to_string();
     tokio::spawn(async move {
-        let mut buf = vec![0u8; frame_bytes];
+        let mut resampler = resampler::Resampler::new(channels);
+        let mut native_buf = vec![0u8; native_frame_bytes.max(1)];
+        let mut carry: VecDeque<i16> = VecDeque::new();
         loop {
             if reader_cancel.is_cancelled() {
                 break;
             }
             let t0 = Instant::now();
-            if stdout.read_exact(&mut buf).await.is_err() {
+            if stdout.read_exact(&mut native_buf).await.is_err() {
                 break;
             }
             let dt = t0.elapsed();
             if dt >= Duration::from_millis(200) {
                 warn!(source_url = %reader_src, read_ms = %dt.as_millis(), "ffmpeg pcm read stalled");
             }
-            if pcm_tx.send(buf.clone()).await.is_err() {
-                break;
+
+            let native_i16: Vec<i16> = native_buf
+                .chunks_exact(2)
+                .map(|b| i16::from_le_bytes([b[0], b[1]]))
+                .collect();
+            let resampled = resampler.process(&native_i16, decode_rate, 48000);
+            carry.extend(resampled);
+
+            while carry.len() >= frame_samples_total {
+                let out_i16: Vec<i16> = carry.drain(..frame_samples_total).collect();
+                let out_bytes: Vec<u8> = out_i16.iter().flat_map(|s| s.to_le_bytes()).collect();
+                if pcm_tx.send(out_bytes).await.is_err() {
+                    return;
+                }
             }
         }
     });
 
+    Ok((child, pcm_rx))
+}
+
+/// Dispatches to either the ffmpeg decoder or the built-in synthetic
+/// [`test_source`], so `playback_loop`'s three restart sites (initial start,
+/// Seek, crossfade-into-next) don't each need to special-case `test:` URLs.
+/// A `test:` source has no real child process, so its `ChildKillOnDrop`
+/// carries no child -- `start_offset_secs`/`max_samplerate` are ignored
+/// since there's no timeline or native rate to work from, only a repeating
+/// deterministic 48kHz signal.
+async fn spawn_decoder(
+    source_url: &str,
+    start_offset_secs: f64,
+    frame_bytes: usize,
+    max_samplerate: u32,
+    cancel: CancellationToken,
+) -> Result<(ChildKillOnDrop, mpsc::Receiver<Vec<u8>>)> {
+    if let Some(spec) = source_url.strip_prefix("test:") {
+        let pcm_rx = test_source::spawn(test_source::TestSignalKind::parse(spec), frame_bytes, cancel);
+        return Ok((ChildKillOnDrop { child: None }, pcm_rx));
+    }
+    spawn_ffmpeg_decoder(source_url, start_offset_secs, frame_bytes, max_samplerate, cancel).await
+}
+
+/// A decoder for the next queued track, spawned ahead of the current
+/// source's real EOF so its PCM can be equal-power crossfaded in. See
+/// `playback_loop`'s EOF handling for how this is created/consumed.
+struct PendingNext {
+    child: ChildKillOnDrop,
+    pcm_rx: mpsc::Receiver<Vec<u8>>,
+    buf: VecDeque<Vec<u8>>,
+    item: QueueItemData,
+}
+
+/// Why `playback_loop` stopped driving `mixer_source`. `stop_internal()`
+/// (called directly by `Stop`, and by `start_track`'s `stop_current` branch
+/// ahead of `Next`/`Previous`/`Skip` starting a new track) cancels the old
+/// loop's token -- whoever cancelled it already owns advancing the queue (or
+/// not advancing it, for a plain `Stop`), so the caller must not also call
+/// `advance_queue_after_finish` in that case. Only a loop that stopped on
+/// its own -- queue exhausted or a decode error -- should trigger that.
+enum PlaybackStop {
+    Finished(Result<()>),
+    Cancelled,
+}
+
+/// Drives playback for `item` and, on a clean end-of-stream, auto-advances
+/// through the queue internally (crossfading into whatever plays next)
+/// rather than returning -- so a whole run of queued tracks with loop mode
+/// `Off`/`Queue` plays out under one task/one mixer registration. Returns
+/// the last track that was current when the loop actually stopped (queue
+/// exhausted, decode error, or cancellation), for the caller to finish
+/// bookkeeping (FINISHED/ERROR event, final queue pop) on -- see
+/// [`PlaybackStop`] for why the cancelled case is kept distinct.
+async fn playback_loop(
+    this: VoiceServiceImpl,
+    mut item: QueueItemData,
+    mixer_source: mixer::MixerSource,
+    mut paused_rx: watch::Receiver<bool>,
+    mut seek_rx: watch::Receiver<Option<i64>>,
+    cancel: CancellationToken,
+    position_ms: Arc<std::sync::atomic::AtomicI64>,
+    buffer_occupancy: Arc<std::sync::atomic::AtomicUsize>,
+) -> (QueueItemData, PlaybackStop) {
+    let status = this.status.clone();
+    let mut source_url = item.source_url.clone();
+    let mut playback_started = Instant::now();
+    info!(source_url = %source_url, "playback starting");
+
+    let frame_samples_per_channel = 48000 / 50;
+    let channels = 2usize;
+    let bytes_per_sample = 2usize;
+    let frame_bytes = frame_samples_per_channel * channels * bytes_per_sample;
+
+    // Sources above this get decoded at this rate instead of their native
+    // one (cheaper than decoding full-res audio just to downsample it);
+    // anything below it is decoded native and resampled to 48kHz. See
+    // `resampler` module docs.
+    let max_samplerate: u32 = get_env("TSBOT_MAX_SAMPLERATE", "48000").parse().unwrap_or(48000);
+
+    let mut decoder_cancel = cancel.child_token();
+    let (mut child, mut pcm_rx) = match spawn_decoder(&source_url, 0.0, frame_bytes, max_samplerate, decoder_cancel.clone()).await {
+        Ok(v) => v,
+        Err(e) => return (item, PlaybackStop::Finished(Err(e))),
+    };
+
+    // Independent of the tick loop below so a slow ffprobe can never stall
+    // audio; re-spawned (old one cancelled) each time the loop crosses into
+    // a new track, same lifecycle as `decoder_cancel`. Mirrored into
+    // `this.track_meta_cancel` so `start_track` can cancel whichever one is
+    // current when this task eventually hands off to the next track.
+    let mut track_meta_cancel = cancel.child_token();
+    *this.track_meta_cancel.lock().await = Some(track_meta_cancel.clone());
+    spawn_track_metadata_task(
+        this.events_tx.clone(),
+        item.source_url.clone(),
+        item.title.clone(),
+        position_ms.clone(),
+        track_meta_cancel.clone(),
+    );
+
+    // Base offset (ms) of the currently running decoder; bumped on each Seek
+    // so `position_ms = current_offset_ms + frames played since then * 20`.
+    let mut current_offset_ms: i64 = 0;
+    let mut frames_played_since_offset: u64 = 0;
+
+    let prebuffer_ms: u64 = get_env("TSBOT_PREBUFFER_MS", "100").parse().unwrap_or(100);
+    let prebuffer_target: usize = (prebuffer_ms / 20).max(1) as usize;
+
+    let frame_duration = Duration::from_millis(20);
+
+    let mut pcm = vec![0u8; frame_bytes];
+    let mut float_buf = vec![0f32; frame_samples_per_channel * channels];
+
+    let mut reverb = SimpleReverb::new();
+    let bass_cutoff_hz: f32 = 150.0;
+    let fs: f32 = 48000.0;
+    let bass_alpha: f32 = (2.0 * std::f32::consts::PI * bass_cutoff_hz)
+        / (fs + 2.0 * std::f32::consts::PI * bass_cutoff_hz);
+    let mut bass_lp_l: f32 = 0.0;
+    let mut bass_lp_r: f32 = 0.0;
+
+    let mut loudness = LoudnessNormalizer::new();
+    let mut limiter = TruePeakLimiter::new();
+
     let mut ticker = tokio::time::interval(frame_duration);
     ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
     let mut underruns_total: u64 = 0;
@@ -1208,8 +3156,6 @@ async fn playback_loop(
     let mut logged_first_pcm = false;
 
     let mut pcm_buf: VecDeque<Vec<u8>> = VecDeque::new();
-
-    let prebuffer_target: usize = 5;
     let mut prebuffering = true;
 
     let mut last_tick = Instant::now();
@@ -1217,20 +3163,56 @@ async fn playback_loop(
     let mut clipped_samples: u64 = 0;
     let mut max_abs_sample: f32 = 0.0;
     let mut diag_next = Instant::now() + Duration::from_secs(5);
+    // CPU-headroom proxy: wall-clock spent doing this tick's work (PCM
+    // pull/FX/encode-adjacent prep, everything between the tick firing and
+    // handing the frame to the mixer) versus the 20ms budget, averaged over
+    // the diagnostic window and reported as `parked_pct` -- the percentage
+    // of the budget left unused. Falling toward 0 means the audio task is
+    // at risk of missing its real-time deadline.
+    const TICK_BUDGET_MS: u128 = 20;
+    let mut tick_busy_ms_total: u128 = 0;
+    let mut tick_count_since_diag: u64 = 0;
 
     let fade_total_samples_per_channel: usize = 48000 / 1000 * 80;
     let mut fade_pos_samples_per_channel: usize = 0;
 
+    // Equal-power crossfade into the next queued track. `decoder_eof` marks
+    // that the current decoder's channel has disconnected (no more PCM is
+    // coming); `crossfade_decided` guards the one-time decision of what
+    // happens next so it only runs once per EOF. While `pending` is `Some`,
+    // every tick mixes this source's (possibly already-empty) tail with the
+    // next decoder's head using gains `cos(theta)`/`sin(theta)` swept over
+    // `crossfade_total_frames` ticks; when no next track is available we
+    // instead fade the tail out to silence over whatever's left buffered.
+    let crossfade_ms: u64 = get_env("TSBOT_CROSSFADE_MS", "3000").parse().unwrap_or(3000);
+    let crossfade_frames_max: usize = (crossfade_ms / 20).max(1) as usize;
+    let mut decoder_eof = false;
+    let mut crossfade_decided = false;
+    let mut pending: Option<PendingNext> = None;
+    let mut crossfade_total_frames: usize = 0;
+    let mut crossfade_done_frames: usize = 0;
+    let mut fade_out_active = false;
+    let mut fade_out_total_frames: usize = 0;
+    let mut fade_out_done_frames: usize = 0;
+
+    // Set just before every `break`/`break 'main` that exits because `cancel`
+    // fired (directly, or via its sender being torn down alongside it) --
+    // distinguishes that from the loop's own natural end-of-queue `break`
+    // below so the caller knows whether it owns the post-loop queue advance.
+    let mut cancelled = false;
+
     'main: loop {
         if cancel.is_cancelled() {
+            cancelled = true;
             break;
         }
 
         while *paused_rx.borrow() {
             tokio::select! {
-                _ = cancel.cancelled() => { break 'main; }
+                _ = cancel.cancelled() => { cancelled = true; break 'main; }
                 r = paused_rx.changed() => {
                     if r.is_err() {
+                        cancelled = true;
                         break 'main;
                     }
                 }
@@ -1238,13 +3220,134 @@ async fn playback_loop(
         }
 
         tokio::select! {
-            _ = cancel.cancelled() => { break; }
+            _ = cancel.cancelled() => { cancelled = true; break; }
             _ = ticker.tick() => {}
         }
 
-        while let Ok(frame) = pcm_rx.try_recv() {
-            if frame.len() == frame_bytes {
-                pcm_buf.push_back(frame);
+        if seek_rx.has_changed().unwrap_or(false) {
+            if let Some(target_ms) = *seek_rx.borrow_and_update() {
+                info!(source_url = %source_url, target_ms, "seeking");
+                decoder_cancel.cancel();
+                if let Some(mut c) = child.child.take() {
+                    let _ = c.start_kill();
+                }
+                decoder_cancel = cancel.child_token();
+                match spawn_decoder(
+                    &source_url,
+                    target_ms as f64 / 1000.0,
+                    frame_bytes,
+                    max_samplerate,
+                    decoder_cancel.clone(),
+                )
+                .await
+                {
+                    Ok((new_child, new_pcm_rx)) => {
+                        child = new_child;
+                        pcm_rx = new_pcm_rx;
+                        pcm_buf.clear();
+                        prebuffering = true;
+                        logged_first_pcm = false;
+                        underruns_consecutive = 0;
+                        current_offset_ms = target_ms;
+                        frames_played_since_offset = 0;
+                        fade_pos_samples_per_channel = 0;
+                        // A seek discards any in-flight crossfade/fade-out --
+                        // the track timeline just jumped, so "nearing the end"
+                        // no longer holds.
+                        decoder_eof = false;
+                        crossfade_decided = false;
+                        pending = None;
+                        crossfade_total_frames = 0;
+                        crossfade_done_frames = 0;
+                        fade_out_active = false;
+                        fade_out_total_frames = 0;
+                        fade_out_done_frames = 0;
+                        position_ms.store(target_ms, std::sync::atomic::Ordering::Relaxed);
+                    }
+                    Err(e) => {
+                        return (item, PlaybackStop::Finished(Err(anyhow!("seek failed to restart decoder: {e}"))));
+                    }
+                }
+            }
+        }
+
+        if !decoder_eof {
+            loop {
+                match pcm_rx.try_recv() {
+                    Ok(frame) => {
+                        if frame.len() == frame_bytes {
+                            pcm_buf.push_back(frame);
+                        }
+                    }
+                    Err(mpsc::error::TryRecvError::Empty) => break,
+                    Err(mpsc::error::TryRecvError::Disconnected) => {
+                        decoder_eof = true;
+                        break;
+                    }
+                }
+            }
+        }
+        if let Some(p) = pending.as_mut() {
+            while let Ok(frame) = p.pcm_rx.try_recv() {
+                if frame.len() == frame_bytes {
+                    p.buf.push_back(frame);
+                }
+            }
+        }
+        buffer_occupancy.store(pcm_buf.len(), std::sync::atomic::Ordering::Relaxed);
+
+        if decoder_eof && !crossfade_decided {
+            crossfade_decided = true;
+            let candidate = { this.queue.lock().await.peek_next(&item) };
+            match candidate {
+                Some(cand) => {
+                    match spawn_decoder(&cand.source_url, 0.0, frame_bytes, max_samplerate, cancel.child_token()).await {
+                        Ok((next_child, next_pcm_rx)) => {
+                            info!(from = %item.source_url, to = %cand.source_url, crossfade_ms, "crossfading into next queued track");
+                            // Commit the queue advance now that the next decoder is
+                            // actually up; until this point a Next/Previous/Stop
+                            // racing in would still see `cand` sitting in the queue.
+                            this.pop_next_for_queue(item.clone()).await;
+                            {
+                                let mut st = status.lock().await;
+                                st.now_playing_title = cand.title.clone();
+                                st.now_playing_source_url = cand.source_url.clone();
+                            }
+                            // PlaybackEvent.Type: STARTED=1
+                            emit_playback(&this.events_tx, 1, cand.title.clone(), cand.source_url.clone(), "");
+                            this.metrics.record_playback_event(1);
+                            track_meta_cancel.cancel();
+                            track_meta_cancel = cancel.child_token();
+                            *this.track_meta_cancel.lock().await = Some(track_meta_cancel.clone());
+                            spawn_track_metadata_task(
+                                this.events_tx.clone(),
+                                cand.source_url.clone(),
+                                cand.title.clone(),
+                                position_ms.clone(),
+                                track_meta_cancel.clone(),
+                            );
+                            pending = Some(PendingNext {
+                                child: next_child,
+                                pcm_rx: next_pcm_rx,
+                                buf: VecDeque::new(),
+                                item: cand,
+                            });
+                            crossfade_total_frames = crossfade_frames_max;
+                            crossfade_done_frames = 0;
+                        }
+                        Err(e) => {
+                            warn!(%e, source_url = %cand.source_url, "failed to start next track for crossfade, finishing current track with a fade-out");
+                            fade_out_active = true;
+                            fade_out_total_frames = pcm_buf.len().max(1);
+                            fade_out_done_frames = 0;
+                        }
+                    }
+                }
+                None => {
+                    fade_out_active = true;
+                    fade_out_total_frames = pcm_buf.len().max(1);
+                    fade_out_done_frames = 0;
+                }
             }
         }
 
@@ -1253,7 +3356,7 @@ async fn playback_loop(
                 logged_first_pcm = true;
                 info!(source_url = %source_url, first_pcm_ms = %playback_started.elapsed().as_millis(), "first pcm frame received");
             } else if playback_started.elapsed() >= Duration::from_secs(5) {
-                return Err(anyhow!("no pcm received from ffmpeg"));
+                return (item, PlaybackStop::Finished(Err(anyhow!("no pcm received from decoder"))));
             }
         }
 
@@ -1278,7 +3381,7 @@ async fn playback_loop(
                     pcm.copy_from_slice(&frame);
                     got_real_frame = true;
                 }
-            } else {
+            } else if !decoder_eof {
                 match tokio::time::timeout(Duration::from_millis(3), pcm_rx.recv()).await {
                     Ok(Some(frame)) => {
                         if frame.len() == frame_bytes {
@@ -1287,38 +3390,69 @@ async fn playback_loop(
                         }
                     }
                     Ok(None) => {
-                        // ffmpeg finished / failed. Stop playback.
-                        break;
+                        decoder_eof = true;
                     }
                     Err(_) => {}
                 }
             }
         }
+        if !got_real_frame {
+            pcm.fill(0);
+        }
+
+        // Pull this tick's head of the crossfade target, if one is running.
+        let pcm_next: Option<Vec<u8>> = pending.as_mut().and_then(|p| {
+            p.buf
+                .pop_front()
+                .filter(|frame| frame.len() == frame_bytes)
+        });
 
-        if got_real_frame {
+        // Underrun accounting looks at whichever source is actually feeding
+        // the mix this tick, so a healthy crossfade (old exhausted, new
+        // still prebuffering) isn't mistaken for a stall.
+        if got_real_frame || pcm_next.is_some() {
             underruns_consecutive = 0;
         } else {
-            pcm.fill(0);
             underruns_total += 1;
             underruns_window += 1;
             underruns_consecutive += 1;
         }
 
+        frames_played_since_offset += 1;
+        position_ms.store(
+            current_offset_ms + (frames_played_since_offset as i64 * 20),
+            std::sync::atomic::Ordering::Relaxed,
+        );
+
         // If we keep sending silence for too long, treat it as a playback failure.
         // The backend will auto-skip/delete on Playback ERROR.
         if logged_first_pcm && underruns_consecutive >= 150 {
-            return Err(anyhow!(
-                "sustained pcm underrun ({} frames, {} ms)",
-                underruns_consecutive,
-                underruns_consecutive * 20
-            ));
+            return (
+                item,
+                PlaybackStop::Finished(Err(anyhow!(
+                    "sustained pcm underrun ({} frames, {} ms)",
+                    underruns_consecutive,
+                    underruns_consecutive * 20
+                ))),
+            );
         }
 
         if underruns_total > 0 && underruns_total % 50 == 0 {
             info!(underruns_total = %underruns_total, "playback underrun (sending silence frames to keep cadence)");
         }
 
-        let (vol, fx_pan, fx_width, fx_swap_lr, fx_bass_db, fx_reverb_mix) = {
+        let (
+            vol,
+            fx_pan,
+            fx_width,
+            fx_swap_lr,
+            fx_bass_db,
+            fx_reverb_mix,
+            loudness_enabled,
+            loudness_target_lufs,
+            loudness_max_gain_db,
+            limiter_ceiling_db,
+        ) = {
             let st = status.lock().await;
             let r = (st.volume_percent as f32 / 100.0).clamp(0.0, 2.0);
             let vol = if r <= 1.0 { r.powf(1.6) } else { r };
@@ -1329,18 +3463,48 @@ async fn playback_loop(
                 st.fx_swap_lr,
                 st.fx_bass_db.clamp(0.0, 18.0),
                 st.fx_reverb_mix.clamp(0.0, 1.0),
+                st.loudness_enabled,
+                st.loudness_target_lufs.clamp(-40.0, 0.0),
+                st.loudness_max_gain_db.clamp(0.0, 24.0),
+                st.limiter_ceiling_db.clamp(-12.0, 0.0),
             )
         };
 
+        // Equal-power crossfade gains (g_out = cos(theta), g_in = sin(theta),
+        // theta sweeping 0..pi/2), or a plain fade-out when nothing follows.
+        let (g_out, g_in) = if pending.is_some() {
+            let theta = (crossfade_done_frames as f32 / crossfade_total_frames.max(1) as f32)
+                .clamp(0.0, 1.0)
+                * std::f32::consts::FRAC_PI_2;
+            (theta.cos(), theta.sin())
+        } else if fade_out_active {
+            let theta = (fade_out_done_frames as f32 / fade_out_total_frames.max(1) as f32)
+                .clamp(0.0, 1.0)
+                * std::f32::consts::FRAC_PI_2;
+            (theta.cos(), 0.0)
+        } else {
+            (1.0, 0.0)
+        };
+
         for i in 0..(frame_samples_per_channel * channels) {
             let lo = pcm[i * 2];
             let hi = pcm[i * 2 + 1];
-            let s = i16::from_le_bytes([lo, hi]) as f32;
-            let v = (s / 32768.0) * vol;
-            float_buf[i] = v;
+            let old_s = i16::from_le_bytes([lo, hi]) as f32 / 32768.0;
+            let new_s = match pcm_next.as_ref() {
+                Some(next) => i16::from_le_bytes([next[i * 2], next[i * 2 + 1]]) as f32 / 32768.0,
+                None => 0.0,
+            };
+            float_buf[i] = (old_s * g_out + new_s * g_in) * vol;
+        }
+
+        if pending.is_some() {
+            crossfade_done_frames = (crossfade_done_frames + 1).min(crossfade_total_frames);
+        }
+        if fade_out_active {
+            fade_out_done_frames = (fade_out_done_frames + 1).min(fade_out_total_frames);
         }
 
-        if got_real_frame && fade_pos_samples_per_channel < fade_total_samples_per_channel {
+        if pending.is_none() && got_real_frame && fade_pos_samples_per_channel < fade_total_samples_per_channel {
             let denom = fade_total_samples_per_channel as f32;
             for i in 0..frame_samples_per_channel {
                 let s = fade_pos_samples_per_channel + i;
@@ -1353,6 +3517,18 @@ async fn playback_loop(
                 .min(fade_total_samples_per_channel);
         }
 
+        if loudness_enabled {
+            for i in 0..frame_samples_per_channel {
+                let idx = i * 2;
+                let mut l = float_buf[idx];
+                let mut r = float_buf[idx + 1];
+                loudness.measure(l, r);
+                loudness.apply_gain(&mut l, &mut r, loudness_target_lufs, loudness_max_gain_db);
+                float_buf[idx] = l;
+                float_buf[idx + 1] = r;
+            }
+        }
+
         let bass_gain = 10.0_f32.powf(fx_bass_db / 20.0);
         if (bass_gain - 1.0).abs() > 0.0001 || fx_reverb_mix > 0.0001 {
             for i in 0..frame_samples_per_channel {
@@ -1428,20 +3604,31 @@ async fn playback_loop(
             }
         }
 
-        let len = encoder
-            .encode_float(&float_buf, &mut opus_out)
-            .map_err(|e| anyhow!("opus encode failed: {e}"))?;
+        let limiter_ceiling = 10.0_f32.powf(limiter_ceiling_db / 20.0);
+        for i in 0..frame_samples_per_channel {
+            let idx = i * 2;
+            let (l, r) = limiter.process(float_buf[idx], float_buf[idx + 1], limiter_ceiling);
+            float_buf[idx] = l;
+            float_buf[idx + 1] = r;
+        }
 
-        let packet = OutAudio::new(&AudioData::C2S {
-            id: 0,
-            codec: CodecType::OpusMusic,
-            data: &opus_out[..len],
-        });
+        let frame: Vec<i16> = float_buf
+            .iter()
+            .map(|&v| (v.clamp(-1.0, 1.0) * 32767.0) as i16)
+            .collect();
+        if mixer_source.send(frame).await.is_err() {
+            return (item, PlaybackStop::Finished(Err(anyhow!("mixer source closed"))));
+        }
 
-        let _ = ts3_audio_tx.send(packet).await;
+        tick_busy_ms_total += now.elapsed().as_millis();
+        tick_count_since_diag += 1;
 
         if now >= diag_next {
             diag_next = now + Duration::from_secs(5);
+            let budget_ms_total = TICK_BUDGET_MS * tick_count_since_diag.max(1) as u128;
+            let work_ms_total = tick_busy_ms_total.min(budget_ms_total);
+            let parked_pct = (budget_ms_total - work_ms_total) as f64 / budget_ms_total as f64 * 100.0;
+            let limiter_reduction_db = limiter.take_max_reduction_db();
             if underruns_window > 0 || clipped_samples > 0 || tick_jitter_max_ms > 25 {
                 warn!(
                     source_url = %source_url,
@@ -1450,6 +3637,8 @@ async fn playback_loop(
                     tick_jitter_max_ms = %tick_jitter_max_ms,
                     clipped_samples = %clipped_samples,
                     max_abs_sample = %max_abs_sample,
+                    limiter_reduction_db = %format!("{limiter_reduction_db:.1}"),
+                    parked_pct = %format!("{parked_pct:.1}"),
                     "audio_encode_diag"
                 );
             } else {
@@ -1460,6 +3649,8 @@ async fn playback_loop(
                     tick_jitter_max_ms = %tick_jitter_max_ms,
                     clipped_samples = %clipped_samples,
                     max_abs_sample = %max_abs_sample,
+                    limiter_reduction_db = %format!("{limiter_reduction_db:.1}"),
+                    parked_pct = %format!("{parked_pct:.1}"),
                     "audio_encode_diag"
                 );
             }
@@ -1467,22 +3658,62 @@ async fn playback_loop(
             clipped_samples = 0;
             max_abs_sample = 0.0;
             underruns_window = 0;
+            tick_busy_ms_total = 0;
+            tick_count_since_diag = 0;
         }
-    }
 
-    // Signal end-of-stream to clients (flush/stop decoder).
-    let eos = OutAudio::new(&AudioData::C2S {
-        id: 0,
-        codec: CodecType::OpusMusic,
-        data: &[],
-    });
-    let _ = ts3_audio_tx.send(eos).await;
+        // Crossfade window finished: the next decoder becomes the current
+        // one and we keep looping in the same task/mixer registration.
+        if pending.is_some() && crossfade_done_frames >= crossfade_total_frames {
+            let p = pending.take().unwrap();
+            if let Some(mut c) = child.child.take() {
+                let _ = c.start_kill();
+            }
+            child = p.child;
+            pcm_rx = p.pcm_rx;
+            pcm_buf = p.buf;
+            item = p.item;
+            source_url = item.source_url.clone();
+            current_offset_ms = 0;
+            frames_played_since_offset = 0;
+            prebuffering = false;
+            logged_first_pcm = true;
+            playback_started = Instant::now();
+            underruns_consecutive = 0;
+            decoder_eof = false;
+            crossfade_decided = false;
+            crossfade_total_frames = 0;
+            crossfade_done_frames = 0;
+            fade_out_active = false;
+            fade_out_total_frames = 0;
+            fade_out_done_frames = 0;
+            loudness = LoudnessNormalizer::new();
+            info!(source_url = %source_url, "crossfade complete, now current track");
+            continue;
+        }
+
+        // Nothing left to play: old decoder is done, no crossfade target.
+        if decoder_eof && pending.is_none() && pcm_buf.is_empty() {
+            break;
+        }
+    }
 
+    // The mixer drops this source from its mix as soon as `mixer_source` is
+    // dropped below, so there's no separate end-of-stream marker to send.
     if let Some(mut c) = child.child.take() {
         let _ = c.start_kill();
         let _ = c.wait().await;
     }
-    Ok(())
+    if let Some(mut p) = pending.take() {
+        if let Some(mut c) = p.child.child.take() {
+            let _ = c.start_kill();
+        }
+    }
+    if cancelled {
+        (item, PlaybackStop::Cancelled)
+    } else {
+        (item, PlaybackStop::Finished(Ok(())))
+    }
 }
 
 #[tokio::main]
@@ -1494,6 +3725,7 @@ async fn main() -> Result<()> {
     let (ts3_audio_tx, ts3_audio_rx) = mpsc::channel::<OutPacket>(200);
     let (ts3_notice_tx, ts3_notice_rx) = mpsc::channel::<(i32, String)>(50);
     let (ts3_cmd_tx, ts3_cmd_rx) = mpsc::channel::<OutCommand>(50);
+    let (ts3_record_tx, ts3_record_rx) = mpsc::channel::<RecordCommand>(8);
 
     let (events_tx, _events_rx) = broadcast::channel::<voicev1::Event>(512);
 
@@ -1507,11 +3739,34 @@ async fn main() -> Result<()> {
         shutdown_token_clone.cancel();
     });
 
+    let position_ms = Arc::new(std::sync::atomic::AtomicI64::new(0));
+    let buffer_occupancy = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let metrics = metrics::Metrics::new();
+
+    let metrics_addr = get_env("TSBOT_METRICS_ADDR", "");
+    if !metrics_addr.is_empty() {
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            if let Err(e) = metrics::serve(metrics, metrics_addr).await {
+                error!(%e, "metrics endpoint failed");
+            }
+        });
+    }
+
+    let push_gateway = get_env("TSBOT_METRICS_PUSH_GATEWAY", "");
+    if !push_gateway.is_empty() {
+        let metrics = metrics.clone();
+        let interval_secs: u64 = get_env("TSBOT_METRICS_PUSH_INTERVAL_SECS", "15").parse().unwrap_or(15);
+        tokio::spawn(metrics::push_loop(metrics, push_gateway, Duration::from_secs(interval_secs)));
+    }
+
     let ts3_task = {
         let events_tx_clone = events_tx.clone();
         let shutdown_token_clone = shutdown_token.clone();
+        let buffer_occupancy = buffer_occupancy.clone();
+        let metrics = metrics.clone();
         tokio::spawn(async move {
-            if let Err(e) = ts3_actor(ts3_audio_rx, ts3_notice_rx, ts3_cmd_rx, events_tx_clone, shutdown_token_clone).await {
+            if let Err(e) = ts3_actor(ts3_audio_rx, ts3_notice_rx, ts3_cmd_rx, ts3_record_rx, events_tx_clone, shutdown_token_clone, buffer_occupancy, metrics).await {
                 error!(%e, "ts3 actor exited");
             }
         })
@@ -1529,8 +3784,22 @@ async fn main() -> Result<()> {
         fx_swap_lr: false,
         fx_bass_db: 0.0,
         fx_reverb_mix: 0.0,
+        loudness_enabled: false,
+        loudness_target_lufs: -18.0,
+        loudness_max_gain_db: 12.0,
+        limiter_ceiling_db: -1.0,
+        enc_bitrate_bps: 0,
+        enc_vbr: true,
+        enc_fec: false,
+        enc_packet_loss_percent: 0,
+        enc_complexity: 10,
+        enc_application: 2, // AUDIO, matching the pre-existing hardcoded Application::Audio
+        enc_codec: 2,       // OPUS_MUSIC, matching the pre-existing hardcoded CodecType::OpusMusic
+        enc_dtx: false,
     };
 
+    let mut init_queue = QueueState::default();
+
     if let Some(ps) = load_persisted_voice_state(&persist_file) {
         init_status.volume_percent = ps.volume_percent.clamp(0, 200);
         init_status.fx_pan = ps.fx_pan.clamp(-1.0, 1.0);
@@ -1538,8 +3807,41 @@ async fn main() -> Result<()> {
         init_status.fx_swap_lr = ps.fx_swap_lr;
         init_status.fx_bass_db = ps.fx_bass_db.clamp(0.0, 18.0);
         init_status.fx_reverb_mix = ps.fx_reverb_mix.clamp(0.0, 1.0);
+        init_status.loudness_enabled = ps.loudness_enabled;
+        init_status.loudness_target_lufs = ps.loudness_target_lufs.clamp(-40.0, 0.0);
+        init_status.loudness_max_gain_db = ps.loudness_max_gain_db.clamp(0.0, 24.0);
+        init_status.limiter_ceiling_db = ps.limiter_ceiling_db.clamp(-12.0, 0.0);
+        init_status.enc_bitrate_bps = ps.enc_bitrate_bps.max(0);
+        init_status.enc_vbr = ps.enc_vbr;
+        init_status.enc_fec = ps.enc_fec;
+        init_status.enc_packet_loss_percent = ps.enc_packet_loss_percent.clamp(0, 100);
+        init_status.enc_complexity = ps.enc_complexity.clamp(0, 10);
+        init_status.enc_application = if ps.enc_application == 1 { 1 } else { 2 };
+        init_status.enc_codec = if ps.enc_codec == 1 { 1 } else { 2 };
+        init_status.enc_dtx = ps.enc_dtx;
+        init_queue.items = ps.queue_items.into_iter().collect();
+        init_queue.loop_mode = ps.loop_mode;
     }
 
+    let status = Arc::new(Mutex::new(init_status));
+
+    // The mixer becomes the sole producer feeding `ts3_audio_tx`/`ts3_actor`
+    // from here on; individual sources (music playback, the Discord bridge)
+    // register with it instead of encoding/sending on their own. It also
+    // reads `status`'s live encoder fields each tick so `SetEncoderConfig`
+    // takes effect without tearing down playback.
+    let mixer = mixer::spawn(ts3_audio_tx.clone(), status.clone());
+
+    let discord_ingest_enabled = !matches!(get_env("TSBOT_DISCORD_INGEST_ENABLE", "0").as_str(), "0" | "false" | "no");
+    let discord_ingest = if discord_ingest_enabled {
+        let ingest = discord_ingest::DiscordIngest::new();
+        let ingest_source = mixer.register("discord_ingest", 1.0).await;
+        discord_ingest::spawn(ingest.clone(), ingest_source);
+        Some(ingest)
+    } else {
+        None
+    };
+
     let (persist_tx, mut persist_rx) = mpsc::channel::<PersistedVoiceState>(32);
     {
         let persist_file = persist_file.clone();
@@ -1591,16 +3893,39 @@ async fn main() -> Result<()> {
         });
     }
 
+    metrics.volume_percent.store(status.lock().await.volume_percent as i64, std::sync::atomic::Ordering::Relaxed);
+
     let svc = VoiceServiceImpl {
-        status: Arc::new(Mutex::new(init_status)),
+        status,
         playback: Arc::new(Mutex::new(None)),
+        queue: Arc::new(Mutex::new(init_queue)),
         ts3_audio_tx,
         ts3_notice_tx,
         ts3_cmd_tx,
+        ts3_record_tx,
+        mixer,
         events_tx,
         persist_tx,
+        discord_ingest,
+        position_ms,
+        buffer_occupancy,
+        metrics,
+        track_meta_cancel: Arc::new(Mutex::new(None)),
     };
 
+    #[cfg(all(target_os = "linux", feature = "mpris"))]
+    {
+        let mpris_enabled = !matches!(get_env("TSBOT_MPRIS_ENABLE", "1").as_str(), "0" | "false" | "no");
+        if mpris_enabled {
+            let svc_for_mpris = svc.clone();
+            tokio::spawn(async move {
+                if let Err(e) = mpris::start(svc_for_mpris).await {
+                    error!(%e, "failed to start mpris adapter");
+                }
+            });
+        }
+    }
+
     let addr: std::net::SocketAddr = addr.parse()?;
     let listener = tokio::net::TcpListener::bind(addr)
         .await
@@ -1609,6 +3934,7 @@ async fn main() -> Result<()> {
     info!("voice-service listening on {}", listener.local_addr()?);
 
     let server = tonic::transport::Server::builder()
+        .layer(rpc_trace::RpcTraceLayer)
         .add_service(VoiceServiceServer::new(svc))
         .serve_with_incoming_shutdown(
             TcpListenerStream::new(listener),
@@ -1632,5 +3958,6 @@ async fn main() -> Result<()> {
     }
 
     info!("Voice service shutdown complete");
+    logger::shutdown();
     Ok(())
 }
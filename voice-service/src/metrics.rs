@@ -0,0 +1,188 @@
+//! Prometheus metrics for the audio pipeline and playback state. Promotes
+//! the counters `ts3_actor` was already computing for its periodic
+//! `audio_send_diag` log line into shared atomics, served in Prometheus text
+//! format over a small HTTP endpoint (`TSBOT_METRICS_ADDR`) and, optionally,
+//! pushed to a Pushgateway on an interval (`TSBOT_METRICS_PUSH_GATEWAY`).
+
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tracing::{error, warn};
+
+/// All gauges/counters are `Relaxed`-ordered: they're independent scrape
+/// samples, not used to synchronize access to anything else.
+#[derive(Default)]
+pub struct Metrics {
+    pub tracks_started: AtomicU64,
+    pub tracks_finished: AtomicU64,
+    pub tracks_errored: AtomicU64,
+    pub volume_percent: AtomicI64,
+    pub reconnects: AtomicU64,
+    pub connection_up: AtomicU64,
+    pub send_jitter_max_ms: AtomicU64,
+    pub out_buf_max: AtomicU64,
+    pub out_buf_drops: AtomicU64,
+    pub send_audio_errs: AtomicU64,
+}
+
+impl Metrics {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// PlaybackEvent.Type: 1=STARTED, 2=FINISHED, 3=ERROR.
+    pub fn record_playback_event(&self, ty: i32) {
+        match ty {
+            1 => self.tracks_started.fetch_add(1, Ordering::Relaxed),
+            2 => self.tracks_finished.fetch_add(1, Ordering::Relaxed),
+            3 => self.tracks_errored.fetch_add(1, Ordering::Relaxed),
+            _ => 0,
+        };
+    }
+
+    fn render(&self) -> String {
+        let g = |name: &str, help: &str, v: i64| -> String {
+            format!("# HELP {name} {help}\n# TYPE {name} gauge\n{name} {v}\n")
+        };
+        let c = |name: &str, help: &str, v: u64| -> String {
+            format!("# HELP {name} {help}\n# TYPE {name} counter\n{name} {v}\n")
+        };
+
+        let mut out = String::new();
+        out.push_str(&c(
+            "tsbot_tracks_started_total",
+            "Tracks that began playing",
+            self.tracks_started.load(Ordering::Relaxed),
+        ));
+        out.push_str(&c(
+            "tsbot_tracks_finished_total",
+            "Tracks that finished playing normally",
+            self.tracks_finished.load(Ordering::Relaxed),
+        ));
+        out.push_str(&c(
+            "tsbot_tracks_errored_total",
+            "Tracks that stopped due to an error",
+            self.tracks_errored.load(Ordering::Relaxed),
+        ));
+        out.push_str(&g(
+            "tsbot_volume_percent",
+            "Current playback volume, 0-200",
+            self.volume_percent.load(Ordering::Relaxed),
+        ));
+        out.push_str(&c(
+            "tsbot_ts3_reconnects_total",
+            "Successful TS3 reconnects after an established connection was lost",
+            self.reconnects.load(Ordering::Relaxed),
+        ));
+        out.push_str(&g(
+            "tsbot_ts3_connection_up",
+            "1 if currently connected to the TS3 server, else 0",
+            self.connection_up.load(Ordering::Relaxed) as i64,
+        ));
+        out.push_str(&g(
+            "tsbot_send_jitter_max_ms",
+            "Max outbound audio tick jitter observed in the last diagnostic window",
+            self.send_jitter_max_ms.load(Ordering::Relaxed) as i64,
+        ));
+        out.push_str(&g(
+            "tsbot_out_buf_max",
+            "High-water mark of the outbound audio packet queue in the last diagnostic window",
+            self.out_buf_max.load(Ordering::Relaxed) as i64,
+        ));
+        out.push_str(&c(
+            "tsbot_out_buf_drops_total",
+            "Outbound audio packets dropped due to queue overflow (current connection)",
+            self.out_buf_drops.load(Ordering::Relaxed),
+        ));
+        out.push_str(&c(
+            "tsbot_send_audio_errs_total",
+            "send_audio() failures (current connection)",
+            self.send_audio_errs.load(Ordering::Relaxed),
+        ));
+        out
+    }
+}
+
+/// Serves `GET /metrics` (any path, really) in Prometheus text format.
+/// Runs until the listener fails to bind; individual connection errors are
+/// logged and otherwise ignored.
+pub async fn serve(metrics: Arc<Metrics>, addr: String) -> anyhow::Result<()> {
+    let listener = TcpListener::bind(&addr).await?;
+    tracing::info!(%addr, "metrics endpoint listening");
+
+    loop {
+        let (mut stream, _) = match listener.accept().await {
+            Ok(s) => s,
+            Err(e) => {
+                warn!(%e, "metrics: accept failed");
+                continue;
+            }
+        };
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 1024];
+            // We don't care about the request line/headers beyond draining
+            // them; every path returns the same text-format dump.
+            let _ = tokio::time::timeout(Duration::from_secs(2), stream.read(&mut buf)).await;
+
+            let body = metrics.render();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+            let _ = stream.shutdown().await;
+        });
+    }
+}
+
+/// Periodically POSTs the same text dump to a Pushgateway, mirroring what
+/// spoticord does for short-lived/NAT'd deployments where scraping in isn't
+/// an option.
+pub async fn push_loop(metrics: Arc<Metrics>, gateway_url: String, interval: Duration) {
+    let Some((host, port, path)) = parse_http_url(&gateway_url) else {
+        error!(url = %gateway_url, "metrics: invalid TSBOT_METRICS_PUSH_GATEWAY url, push disabled");
+        return;
+    };
+
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let body = metrics.render();
+        let request = format!(
+            "POST {path} HTTP/1.1\r\nHost: {host}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+
+        match tokio::net::TcpStream::connect((host.as_str(), port)).await {
+            Ok(mut stream) => {
+                if let Err(e) = stream.write_all(request.as_bytes()).await {
+                    warn!(%e, "metrics: pushgateway write failed");
+                }
+            }
+            Err(e) => {
+                warn!(%e, gateway = %gateway_url, "metrics: pushgateway connect failed");
+            }
+        }
+    }
+}
+
+/// Parses a `http://host[:port]/path` string. No https/query-string support:
+/// the Pushgateway push path is an optional convenience, not a general HTTP client.
+fn parse_http_url(url: &str) -> Option<(String, u16, String)> {
+    let rest = url.strip_prefix("http://")?;
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    let (host, port) = match authority.split_once(':') {
+        Some((h, p)) => (h.to_string(), p.parse().ok()?),
+        None => (authority.to_string(), 80),
+    };
+    Some((host, port, path.to_string()))
+}
@@ -0,0 +1,279 @@
+//! Central PCM mixer sitting between audio producers (music playback, the
+//! Discord ingest bridge in [`crate::discord_ingest`], and any future
+//! source) and the single outbound Opus-encoded TS3 voice stream.
+//!
+//! TS3 only carries one outbound Opus stream per client, so two producers
+//! calling `con.send_audio` directly would just fight over it. Instead each
+//! producer [`MixerHandle::register`]s and gets a [`MixerSource`] to push
+//! 20ms 48kHz stereo s16le PCM frames through. Every 20ms the mixer samples
+//! whatever has arrived from each registered source since the last tick
+//! (nothing counts as silence for that source this tick), sums the frames
+//! gain-scaled and clamped to i16 range, Opus-encodes the result once, and
+//! queues it to `ts3_audio_tx` -- the mixer is the only writer of that
+//! channel from this point on.
+//!
+//! A source drops out of the mix automatically once its `MixerSource` (and
+//! therefore its channel sender) is dropped, e.g. when `playback_loop`
+//! returns at the end of a track.
+//!
+//! The encoder itself is reconfigured in place every tick from
+//! `SharedStatus`'s `enc_*` fields (bitrate, VBR, FEC, complexity, codec,
+//! DTX) so `SetEncoderConfig` takes effect live; only an `application`
+//! change forces a full `Encoder` rebuild, since libopus fixes that mode at
+//! creation.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use audiopus::coder::Encoder;
+use audiopus::{Application, Bitrate};
+use tokio::sync::{mpsc, Mutex};
+use tracing::{info, warn};
+use tsproto_packets::packets::{AudioData, CodecType, OutAudio, OutPacket};
+
+use crate::SharedStatus;
+
+/// Snapshot of `SharedStatus`'s `enc_*` fields the mixer compares against
+/// each tick to decide whether the encoder needs reconfiguring.
+#[derive(Clone, Copy, PartialEq)]
+struct EncoderConfig {
+    bitrate_bps: i32,
+    vbr: bool,
+    fec: bool,
+    packet_loss_percent: i32,
+    complexity: i32,
+    application: i32,
+    codec: i32,
+    dtx: bool,
+}
+
+impl EncoderConfig {
+    fn from_status(st: &SharedStatus) -> Self {
+        Self {
+            bitrate_bps: st.enc_bitrate_bps,
+            vbr: st.enc_vbr,
+            fec: st.enc_fec,
+            packet_loss_percent: st.enc_packet_loss_percent,
+            complexity: st.enc_complexity,
+            application: st.enc_application,
+            codec: st.enc_codec,
+            dtx: st.enc_dtx,
+        }
+    }
+
+    fn opus_application(&self) -> Application {
+        if self.application == 1 {
+            Application::Voip
+        } else {
+            Application::Audio
+        }
+    }
+
+    fn codec_type(&self) -> CodecType {
+        if self.codec == 1 {
+            CodecType::OpusVoice
+        } else {
+            CodecType::OpusMusic
+        }
+    }
+
+    /// Applies every setting but `application` (which Opus can only fix at
+    /// encoder creation) to an already-built encoder.
+    fn apply_tunables(&self, encoder: &mut Encoder) {
+        let bitrate = if self.bitrate_bps > 0 {
+            Bitrate::BitsPerSecond(self.bitrate_bps)
+        } else {
+            Bitrate::Auto
+        };
+        if let Err(e) = encoder.set_bitrate(bitrate) {
+            warn!(%e, "mixer: set_bitrate failed");
+        }
+        if let Err(e) = encoder.set_vbr(self.vbr) {
+            warn!(%e, "mixer: set_vbr failed");
+        }
+        if let Err(e) = encoder.set_inband_fec(self.fec) {
+            warn!(%e, "mixer: set_inband_fec failed");
+        }
+        if let Err(e) = encoder.set_packet_loss_perc(self.packet_loss_percent.clamp(0, 100) as u8) {
+            warn!(%e, "mixer: set_packet_loss_perc failed");
+        }
+        if let Err(e) = encoder.set_complexity(self.complexity as u8) {
+            warn!(%e, "mixer: set_complexity failed");
+        }
+        if let Err(e) = encoder.set_dtx(self.dtx) {
+            warn!(%e, "mixer: set_dtx failed");
+        }
+    }
+}
+
+fn new_encoder(cfg: &EncoderConfig) -> Result<Encoder, audiopus::Error> {
+    let mut encoder = Encoder::new(audiopus::SampleRate::Hz48000, audiopus::Channels::Stereo, cfg.opus_application())?;
+    cfg.apply_tunables(&mut encoder);
+    Ok(encoder)
+}
+
+const FRAME_SAMPLES_PER_CHANNEL: usize = 48000 / 50;
+const CHANNELS: usize = 2;
+/// One 20ms 48kHz stereo frame, interleaved.
+pub const FRAME_SAMPLES: usize = FRAME_SAMPLES_PER_CHANNEL * CHANNELS;
+
+/// Handle a registered source uses to push frames and adjust its own gain.
+#[derive(Clone)]
+pub struct MixerSource {
+    tx: mpsc::Sender<Vec<i16>>,
+    gain: Arc<AtomicU32>,
+}
+
+impl MixerSource {
+    pub async fn send(&self, frame: Vec<i16>) -> Result<(), mpsc::error::SendError<Vec<i16>>> {
+        self.tx.send(frame).await
+    }
+
+    #[allow(dead_code)]
+    pub fn set_gain(&self, gain: f32) {
+        self.gain.store(gain.to_bits(), Ordering::Relaxed);
+    }
+}
+
+struct RegisteredSource {
+    name: String,
+    rx: mpsc::Receiver<Vec<i16>>,
+    gain: Arc<AtomicU32>,
+}
+
+enum Control {
+    Register(RegisteredSource),
+}
+
+/// Handle used to register new sources with a running mixer.
+#[derive(Clone)]
+pub struct MixerHandle {
+    control_tx: mpsc::Sender<Control>,
+}
+
+impl MixerHandle {
+    /// Registers a new source under `name` (used only for logging) with an
+    /// initial linear gain, returning the handle the source pushes frames
+    /// through.
+    pub async fn register(&self, name: &str, initial_gain: f32) -> MixerSource {
+        let (tx, rx) = mpsc::channel::<Vec<i16>>(4);
+        let gain = Arc::new(AtomicU32::new(initial_gain.to_bits()));
+        let source = MixerSource {
+            tx,
+            gain: gain.clone(),
+        };
+        let _ = self
+            .control_tx
+            .send(Control::Register(RegisteredSource {
+                name: name.to_string(),
+                rx,
+                gain,
+            }))
+            .await;
+        source
+    }
+}
+
+/// Spawns the mixer task and returns the handle used to register sources.
+/// `status` is read every tick for the live-tunable encoder pipeline
+/// (bitrate, VBR, FEC, complexity, application, codec, DTX) set via
+/// `SetEncoderConfig`.
+pub fn spawn(ts3_audio_tx: mpsc::Sender<OutPacket>, status: Arc<Mutex<SharedStatus>>) -> MixerHandle {
+    let (control_tx, mut control_rx) = mpsc::channel::<Control>(8);
+
+    tokio::spawn(async move {
+        let mut sources: Vec<RegisteredSource> = Vec::new();
+
+        let mut enc_cfg = EncoderConfig::from_status(&*status.lock().await);
+        let mut encoder = match new_encoder(&enc_cfg) {
+            Ok(e) => e,
+            Err(e) => {
+                warn!(%e, "mixer: opus encoder init failed, mixer disabled");
+                return;
+            }
+        };
+
+        let mut accum = [0i32; FRAME_SAMPLES];
+        let mut opus_out = [0u8; 1275];
+        let mut tick = tokio::time::interval(Duration::from_millis(20));
+
+        loop {
+            tokio::select! {
+                ctrl = control_rx.recv() => {
+                    match ctrl {
+                        Some(Control::Register(src)) => {
+                            info!(source = %src.name, "mixer: source registered");
+                            sources.push(src);
+                        }
+                        None => break,
+                    }
+                }
+
+                _ = tick.tick() => {
+                    let new_cfg = EncoderConfig::from_status(&*status.lock().await);
+                    if new_cfg != enc_cfg {
+                        if new_cfg.application != enc_cfg.application {
+                            // Opus application mode is fixed at creation time,
+                            // so a change here means rebuilding the encoder.
+                            match new_encoder(&new_cfg) {
+                                Ok(e) => encoder = e,
+                                Err(e) => warn!(%e, "mixer: opus encoder re-init failed, keeping previous config"),
+                            }
+                        } else {
+                            new_cfg.apply_tunables(&mut encoder);
+                        }
+                        enc_cfg = new_cfg;
+                    }
+
+                    for s in accum.iter_mut() {
+                        *s = 0;
+                    }
+
+                    let mut delivered_any = false;
+                    sources.retain_mut(|src| match src.rx.try_recv() {
+                        Ok(frame) => {
+                            delivered_any = true;
+                            let gain = f32::from_bits(src.gain.load(Ordering::Relaxed));
+                            let n = frame.len().min(FRAME_SAMPLES);
+                            for i in 0..n {
+                                accum[i] += (frame[i] as f32 * gain) as i32;
+                            }
+                            true
+                        }
+                        Err(mpsc::error::TryRecvError::Empty) => true,
+                        Err(mpsc::error::TryRecvError::Disconnected) => {
+                            info!(source = %src.name, "mixer: source disconnected");
+                            false
+                        }
+                    });
+
+                    if !delivered_any {
+                        continue;
+                    }
+
+                    let mixed: Vec<f32> = accum
+                        .iter()
+                        .map(|&s| s.clamp(i16::MIN as i32, i16::MAX as i32) as f32 / 32768.0)
+                        .collect();
+
+                    match encoder.encode_float(&mixed, &mut opus_out) {
+                        Ok(len) => {
+                            let packet = OutAudio::new(&AudioData::C2S {
+                                id: 0,
+                                codec: enc_cfg.codec_type(),
+                                data: &opus_out[..len],
+                            });
+                            let _ = ts3_audio_tx.send(packet).await;
+                        }
+                        Err(e) => {
+                            warn!(%e, "mixer: opus encode failed");
+                        }
+                    }
+                }
+            }
+        }
+    });
+
+    MixerHandle { control_tx }
+}
@@ -0,0 +1,217 @@
+//! MPRIS MediaPlayer2 D-Bus adapter. Exposes the bot's transport controls
+//! over the standard `org.mpris.MediaPlayer2`/`org.mpris.MediaPlayer2.Player`
+//! interfaces as a thin wrapper over `VoiceServiceImpl`, so desktop tools
+//! such as `playerctl` or i3blocks can drive and observe playback without
+//! going through gRPC. Linux-only: a session bus isn't meaningful elsewhere.
+
+use std::collections::HashMap;
+
+use tokio::sync::broadcast::error::RecvError;
+use tonic::Request;
+use tracing::{error, info};
+use zbus::zvariant::Value;
+use zbus::{interface, ConnectionBuilder, SignalEmitter};
+
+use crate::{voicev1, VoiceServiceImpl};
+
+const BUS_NAME: &str = "org.mpris.MediaPlayer2.tsbot";
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+
+struct MprisRoot;
+
+#[interface(name = "org.mpris.MediaPlayer2")]
+impl MprisRoot {
+    #[zbus(property)]
+    fn can_quit(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn can_raise(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn has_track_list(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn identity(&self) -> String {
+        "TSBot".to_string()
+    }
+
+    #[zbus(property)]
+    fn supported_uri_schemes(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    #[zbus(property)]
+    fn supported_mime_types(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+struct MprisPlayer {
+    svc: VoiceServiceImpl,
+}
+
+#[interface(name = "org.mpris.MediaPlayer2.Player")]
+impl MprisPlayer {
+    async fn play(&self) {
+        let _ = self.svc.resume(Request::new(voicev1::Empty {})).await;
+    }
+
+    async fn pause(&self) {
+        let _ = self.svc.pause(Request::new(voicev1::Empty {})).await;
+    }
+
+    async fn play_pause(&self) {
+        let playing = self.svc.status.lock().await.state == 2;
+        if playing {
+            let _ = self.svc.pause(Request::new(voicev1::Empty {})).await;
+        } else {
+            let _ = self.svc.resume(Request::new(voicev1::Empty {})).await;
+        }
+    }
+
+    async fn stop(&self) {
+        let _ = self.svc.stop(Request::new(voicev1::Empty {})).await;
+    }
+
+    async fn next(&self) {
+        let _ = self.svc.next(Request::new(voicev1::Empty {})).await;
+    }
+
+    async fn previous(&self) {
+        let _ = self.svc.previous(Request::new(voicev1::Empty {})).await;
+    }
+
+    #[zbus(property)]
+    async fn playback_status(&self) -> String {
+        // PlaybackState: 1=IDLE, 2=PLAYING, 3=PAUSED.
+        match self.svc.status.lock().await.state {
+            2 => "Playing",
+            3 => "Paused",
+            _ => "Stopped",
+        }
+        .to_string()
+    }
+
+    #[zbus(property)]
+    async fn volume(&self) -> f64 {
+        (self.svc.status.lock().await.volume_percent as f64 / 100.0).clamp(0.0, 2.0)
+    }
+
+    #[zbus(property)]
+    async fn set_volume(&self, value: f64, #[zbus(signal_emitter)] ctxt: SignalEmitter<'_>) {
+        // Go through the same `SetVolume` RPC path as the gRPC client so a
+        // change made from MPRIS also updates `metrics.volume_percent` and
+        // gets persisted, instead of silently diverging from one made
+        // through gRPC.
+        let volume_percent = (value.clamp(0.0, 2.0) * 100.0).round() as i32;
+        let _ = self
+            .svc
+            .set_volume(Request::new(voicev1::SetVolumeRequest { volume_percent }))
+            .await;
+        if let Err(e) = self.volume_changed(&ctxt).await {
+            error!(%e, "mpris: failed to emit Volume change");
+        }
+    }
+
+    #[zbus(property)]
+    async fn metadata(&self) -> HashMap<String, Value<'_>> {
+        let st = self.svc.status.lock().await;
+        let mut map = HashMap::new();
+        map.insert(
+            "mpris:trackid".to_string(),
+            Value::from(format!("{OBJECT_PATH}/track/current")),
+        );
+        map.insert("xesam:title".to_string(), Value::from(st.now_playing_title.clone()));
+        map.insert("xesam:url".to_string(), Value::from(st.now_playing_source_url.clone()));
+        map
+    }
+
+    #[zbus(property)]
+    fn can_go_next(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_go_previous(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_play(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_pause(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_seek(&self) -> bool {
+        false
+    }
+}
+
+/// Starts the MPRIS D-Bus service and a background task that emits
+/// `PropertiesChanged` whenever a `PlaybackEvent` crosses `svc.events_tx`, so
+/// MPRIS-aware controllers stay in sync without polling.
+pub async fn start(svc: VoiceServiceImpl) -> anyhow::Result<()> {
+    let mut events_rx = svc.events_tx.subscribe();
+    let player = MprisPlayer { svc };
+
+    let connection = ConnectionBuilder::session()?
+        .name(BUS_NAME)?
+        .serve_at(OBJECT_PATH, MprisRoot)?
+        .serve_at(OBJECT_PATH, player)?
+        .build()
+        .await?;
+
+    tokio::spawn(async move {
+        let iface_ref = match connection
+            .object_server()
+            .interface::<_, MprisPlayer>(OBJECT_PATH)
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!(%e, "mpris: failed to obtain player interface reference");
+                return;
+            }
+        };
+
+        loop {
+            match events_rx.recv().await {
+                Ok(ev) => match ev.payload {
+                    Some(voicev1::event::Payload::Playback(_)) => {
+                        let iface = iface_ref.get().await;
+                        if let Err(e) = iface.playback_status_changed(iface_ref.signal_emitter()).await {
+                            error!(%e, "mpris: failed to emit PlaybackStatus change");
+                        }
+                    }
+                    // The Playback arm above only refreshes PlaybackStatus;
+                    // a new track's title/URL is announced here, once its
+                    // TrackMetadataEvent arrives. `test:` sources and failed
+                    // probes emit none, so Metadata isn't re-announced for them.
+                    Some(voicev1::event::Payload::Metadata(_)) => {
+                        let iface = iface_ref.get().await;
+                        if let Err(e) = iface.metadata_changed(iface_ref.signal_emitter()).await {
+                            error!(%e, "mpris: failed to emit Metadata change");
+                        }
+                    }
+                    _ => {}
+                },
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+
+    info!(bus_name = %BUS_NAME, "mpris adapter started");
+    Ok(())
+}
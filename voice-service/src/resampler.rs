@@ -0,0 +1,152 @@
+//! Band-limited linear sample-rate converter used to bring a decoded
+//! source's native rate up (or down, via `max_samplerate`) to the fixed
+//! 48kHz the FX/encode chain requires, without needing ffmpeg's `-ar` to do
+//! the full conversion itself. `spawn_ffmpeg_decoder` still asks ffmpeg for
+//! `min(native_rate, max_samplerate)` so a source well above the cap (e.g.
+//! 96kHz) isn't decoded at full resolution for nothing, but the last leg up
+//! (or down) to 48kHz always happens here.
+//!
+//! State -- the fractional read position, the low-pass filter's running
+//! value, and the last filtered sample -- is kept per channel across calls
+//! so successive 20ms frames interpolate smoothly instead of clicking at
+//! frame boundaries.
+
+struct ChannelState {
+    /// Fractional read position into the *next* call's input, relative to
+    /// its start; carries the sub-sample remainder across frame boundaries.
+    /// Ranges down to -1.0, where -1.0 is `prev_filtered`.
+    frac_pos: f64,
+    /// Last low-pass-filtered sample produced by the previous call, used as
+    /// the left interpolation neighbour for this call's first output.
+    prev_filtered: f32,
+    /// One-pole low-pass filter state (only advanced while downsampling).
+    lp_y: f32,
+}
+
+/// Per-channel linear resampler with a one-pole anti-aliasing low-pass
+/// engaged only when downsampling.
+pub struct Resampler {
+    channels: usize,
+    states: Vec<ChannelState>,
+}
+
+impl Resampler {
+    pub fn new(channels: usize) -> Self {
+        Self {
+            channels,
+            states: (0..channels)
+                .map(|_| ChannelState {
+                    frac_pos: -1.0,
+                    prev_filtered: 0.0,
+                    lp_y: 0.0,
+                })
+                .collect(),
+        }
+    }
+
+    /// Converts one frame of interleaved `in_rate` PCM to interleaved
+    /// `out_rate` PCM. The caller re-chunks the (generally non-round)
+    /// output length back into fixed-size 20ms frames.
+    pub fn process(&mut self, input: &[i16], in_rate: u32, out_rate: u32) -> Vec<i16> {
+        if in_rate == out_rate || input.is_empty() {
+            return input.to_vec();
+        }
+
+        let frames_in = input.len() / self.channels;
+        if frames_in == 0 {
+            return Vec::new();
+        }
+
+        let downsampling = out_rate < in_rate;
+        let lp_alpha = if downsampling {
+            let cutoff_hz = out_rate as f32 * 0.45;
+            let dt = 1.0 / in_rate as f32;
+            let rc = 1.0 / (2.0 * std::f32::consts::PI * cutoff_hz);
+            dt / (rc + dt)
+        } else {
+            1.0
+        };
+        let ratio = in_rate as f64 / out_rate as f64;
+
+        let mut interleaved = Vec::new();
+
+        for ch in 0..self.channels {
+            // Low-pass this channel's slice of the call's input up front so
+            // the interpolation loop below just indexes into it.
+            let mut filtered = Vec::with_capacity(frames_in);
+            let mut y = self.states[ch].lp_y;
+            for f in 0..frames_in {
+                let raw = input[f * self.channels + ch] as f32;
+                if downsampling {
+                    y += lp_alpha * (raw - y);
+                    filtered.push(y);
+                } else {
+                    filtered.push(raw);
+                }
+            }
+            self.states[ch].lp_y = y;
+
+            let mut pos = self.states[ch].frac_pos;
+            let prev = self.states[ch].prev_filtered;
+            let mut channel_out = Vec::new();
+            // Stop short of the last input sample so positions between it and
+            // the next call's first sample are interpolated next call, via
+            // `prev_filtered`.
+            while pos < (frames_in - 1) as f64 {
+                let idx = pos.floor() as isize;
+                let frac = (pos - idx as f64) as f32;
+                let s0 = if idx < 0 { prev } else { filtered[idx as usize] };
+                let s1 = if idx + 1 < frames_in as isize {
+                    filtered[(idx + 1) as usize]
+                } else {
+                    filtered[frames_in - 1]
+                };
+                let sample = s0 + (s1 - s0) * frac;
+                channel_out.push(sample.clamp(i16::MIN as f32, i16::MAX as f32) as i16);
+                pos += ratio;
+            }
+            self.states[ch].frac_pos = pos - frames_in as f64;
+            self.states[ch].prev_filtered = *filtered.last().unwrap_or(&prev);
+
+            if interleaved.is_empty() {
+                interleaved = vec![0i16; channel_out.len() * self.channels];
+            }
+            for (f, sample) in channel_out.into_iter().enumerate() {
+                interleaved[f * self.channels + ch] = sample;
+            }
+        }
+
+        interleaved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Resampler;
+
+    #[test]
+    fn identity_when_rates_match() {
+        let mut r = Resampler::new(1);
+        let input = vec![100i16, -200, 300, -400];
+        let out = r.process(&input, 48000, 48000);
+        assert_eq!(out, input);
+    }
+
+    #[test]
+    fn upsampling_doubles_frame_count() {
+        let mut r = Resampler::new(1);
+        let input: Vec<i16> = (0..480).map(|i| ((i as f32 * 0.1).sin() * 10000.0) as i16).collect();
+        let out = r.process(&input, 24000, 48000);
+        assert_eq!(out.len(), 960);
+    }
+
+    #[test]
+    fn downsampling_halves_frame_count_and_preserves_dc() {
+        let mut r = Resampler::new(1);
+        let input = vec![1000i16; 960];
+        let out = r.process(&input, 48000, 24000);
+        assert_eq!(out.len(), 480);
+        let mean = out.iter().map(|&s| s as f32).sum::<f32>() / out.len() as f32;
+        assert!((mean - 1000.0).abs() < 50.0, "mean {mean}");
+    }
+}
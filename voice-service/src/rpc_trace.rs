@@ -0,0 +1,140 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use http_body::Body;
+use tower::{Layer, Service};
+use tracing::Instrument;
+
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Tower layer that wraps every incoming gRPC call in a tracing span carrying
+/// the method name and a per-request id, and logs entry/exit with the elapsed
+/// duration. Apply it to the tonic `Server` builder so every voice RPC
+/// produces a consistent latency log line, no matter which handler runs.
+#[derive(Clone, Default)]
+pub struct RpcTraceLayer;
+
+impl<S> Layer<S> for RpcTraceLayer {
+    type Service = RpcTraceService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RpcTraceService { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct RpcTraceService<S> {
+    inner: S,
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for RpcTraceService<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: std::fmt::Display,
+    ReqBody: Send + 'static,
+    ResBody: Body + Unpin + Send + 'static,
+    ResBody::Error: std::fmt::Display,
+{
+    type Response = http::Response<TracedBody<ResBody>>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        let request_id = NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+        let method = req.uri().path().to_string();
+        let span = tracing::info_span!("grpc_request", method = %method, request_id);
+
+        // Swap in a ready clone so the call that actually runs was polled via
+        // `poll_ready`, matching the usual tower middleware pattern.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let start = Instant::now();
+
+        let fut = async move {
+            tracing::debug!(request_id, "grpc call started");
+            let result = inner.call(req).await;
+
+            match result {
+                Ok(resp) => {
+                    // `grpc-status` for a handler's `Err(Status)` only shows
+                    // up in the response trailers, produced once the body
+                    // finishes -- reading `resp.headers()` here would just
+                    // see the initial "200 OK" headers and always log status
+                    // 0. Wrap the body so we log once the trailers (and
+                    // therefore the real grpc-status) are actually in hand.
+                    Ok(resp.map(|body| TracedBody {
+                        inner: body,
+                        request_id,
+                        start,
+                    }))
+                }
+                Err(e) => {
+                    let elapsed_ms = start.elapsed().as_millis();
+                    tracing::error!(request_id, error = %e, elapsed_ms, "grpc call failed");
+                    Err(e)
+                }
+            }
+        }
+        .instrument(span);
+
+        Box::pin(fut)
+    }
+}
+
+/// Wraps a gRPC response body so the "grpc call completed" log line can fire
+/// once trailers (carrying the real `grpc-status`) arrive, instead of right
+/// after the initial headers -- see [`RpcTraceService::call`].
+pub struct TracedBody<B> {
+    inner: B,
+    request_id: u64,
+    start: Instant,
+}
+
+impl<B> Body for TracedBody<B>
+where
+    B: Body + Unpin,
+    B::Error: std::fmt::Display,
+{
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        Pin::new(&mut self.inner).poll_data(cx)
+    }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<http::HeaderMap>, Self::Error>> {
+        let trailers = std::task::ready!(Pin::new(&mut self.inner).poll_trailers(cx))?;
+
+        let status = trailers
+            .as_ref()
+            .and_then(|t| t.get("grpc-status"))
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("0");
+        let elapsed_ms = self.start.elapsed().as_millis();
+        tracing::info!(request_id = self.request_id, status, elapsed_ms, "grpc call completed");
+
+        Poll::Ready(Ok(trailers))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
@@ -0,0 +1,108 @@
+//! Built-in deterministic PCM sources selectable in place of ffmpeg via a
+//! `test:<kind>` source_url (e.g. `test:sine:440`, `test:sweep`,
+//! `test:counter`), so the `audio_encode_diag` path in `playback_loop` --
+//! underruns, clipping, tick jitter, parked% -- can be exercised end-to-end
+//! without a live stream. `counter` mode additionally lets a receiver detect
+//! dropped or duplicated frames: any gap or repeat in the sample sequence is
+//! a discontinuity.
+
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+const SAMPLE_RATE: f32 = 48000.0;
+const CHANNELS: usize = 2;
+const SWEEP_PERIOD_SECS: f32 = 10.0;
+const SWEEP_F_LO_HZ: f32 = 20.0;
+const SWEEP_F_HI_HZ: f32 = 20_000.0;
+
+/// Which deterministic signal a `test:` source_url generates.
+#[derive(Clone, Copy)]
+pub enum TestSignalKind {
+    /// Fixed-frequency sine tone on both channels.
+    Sine { hz: f32 },
+    /// Logarithmic sweep from `SWEEP_F_LO_HZ` to `SWEEP_F_HI_HZ` over
+    /// `SWEEP_PERIOD_SECS`, repeating.
+    Sweep,
+    /// A monotonically increasing, wrapping i16 sample counter -- no audio
+    /// content, just a sequence a decoder can check for continuity.
+    Counter,
+}
+
+impl TestSignalKind {
+    /// Parses the part of a `test:` source_url after the scheme, e.g.
+    /// `sine:440`, `sweep`, `counter`. Falls back to a 440Hz sine on
+    /// anything unrecognized so a typo doesn't silently kill playback.
+    pub fn parse(spec: &str) -> Self {
+        let mut parts = spec.splitn(2, ':');
+        match parts.next().unwrap_or("") {
+            "sweep" => TestSignalKind::Sweep,
+            "counter" => TestSignalKind::Counter,
+            _ => {
+                let hz = parts.next().and_then(|v| v.parse().ok()).unwrap_or(440.0);
+                TestSignalKind::Sine { hz }
+            }
+        }
+    }
+}
+
+/// Spawns the generator task and returns the channel `playback_loop` reads
+/// frames from, mirroring `spawn_ffmpeg_decoder`'s `pcm_rx` so the two are
+/// interchangeable from the caller's point of view.
+pub fn spawn(kind: TestSignalKind, frame_bytes: usize, cancel: CancellationToken) -> mpsc::Receiver<Vec<u8>> {
+    let (tx, rx) = mpsc::channel::<Vec<u8>>(50);
+    let frame_samples_per_channel = frame_bytes / (CHANNELS * 2);
+
+    tokio::spawn(async move {
+        let mut phase: f32 = 0.0;
+        let mut sweep_t: f32 = 0.0;
+        let mut counter: i16 = 0;
+        let mut pcm = vec![0u8; frame_bytes];
+        let mut tick = tokio::time::interval(Duration::from_millis(20));
+
+        loop {
+            if cancel.is_cancelled() {
+                break;
+            }
+            tick.tick().await;
+
+            for i in 0..frame_samples_per_channel {
+                let sample = match kind {
+                    TestSignalKind::Sine { hz } => {
+                        let s = (phase * 2.0 * std::f32::consts::PI).sin();
+                        phase = (phase + hz / SAMPLE_RATE).fract();
+                        (s * i16::MAX as f32 * 0.5) as i16
+                    }
+                    TestSignalKind::Sweep => {
+                        let hz = SWEEP_F_LO_HZ * (SWEEP_F_HI_HZ / SWEEP_F_LO_HZ).powf(sweep_t / SWEEP_PERIOD_SECS);
+                        let s = (phase * 2.0 * std::f32::consts::PI).sin();
+                        phase = (phase + hz / SAMPLE_RATE).fract();
+                        sweep_t += 1.0 / SAMPLE_RATE;
+                        if sweep_t >= SWEEP_PERIOD_SECS {
+                            sweep_t = 0.0;
+                        }
+                        (s * i16::MAX as f32 * 0.5) as i16
+                    }
+                    TestSignalKind::Counter => {
+                        let s = counter;
+                        counter = counter.wrapping_add(1);
+                        s
+                    }
+                };
+                let bytes = sample.to_le_bytes();
+                let idx = i * CHANNELS * 2;
+                for ch in 0..CHANNELS {
+                    pcm[idx + ch * 2] = bytes[0];
+                    pcm[idx + ch * 2 + 1] = bytes[1];
+                }
+            }
+
+            if tx.send(pcm.clone()).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    rx
+}